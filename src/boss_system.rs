@@ -0,0 +1,47 @@
+use super::{
+    initiative_system::TurnClock, Boss, BossAbility, CombatStats, GameLog, Name, RunState,
+};
+use specs::prelude::*;
+
+pub struct BossSystem {}
+
+impl<'a> System<'a> for BossSystem {
+    type SystemData = (
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, TurnClock>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Boss>,
+        WriteStorage<'a, CombatStats>,
+        ReadStorage<'a, Name>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (runstate, clock, mut log, mut bosses, mut combat_stats, names) = data;
+
+        if *runstate != RunState::MonsterTurn || !clock.new_turn() {
+            return;
+        }
+
+        for (boss, stats, name) in (&mut bosses, &mut combat_stats, &names).join() {
+            if stats.hp < 1 {
+                continue;
+            }
+
+            for ability in boss.abilities.iter_mut() {
+                match ability {
+                    BossAbility::Regeneration { amount } => {
+                        stats.hp = i32::min(stats.max_hp, stats.hp + *amount);
+                    }
+                    BossAbility::Enrage { bonus, triggered } => {
+                        if !*triggered && stats.hp <= stats.max_hp / 2 {
+                            *triggered = true;
+                            stats.power += *bonus;
+                            log.entries
+                                .push(format!("{} flies into a rage!", name.name));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
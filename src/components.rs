@@ -6,10 +6,70 @@ use specs::{
     saveload::{ConvertSaveload, Marker},
 };
 use specs_derive::*;
+use std::collections::HashMap;
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum EquipmentSlot {
     Melee,
     Shield,
+    Ranged,
+    Head,
+    Body,
+    Legs,
+    Feet,
+    Hands,
+    Amulet,
+    Ring,
+}
+
+impl EquipmentSlot {
+    pub const ALL: [EquipmentSlot; 10] = [
+        EquipmentSlot::Melee,
+        EquipmentSlot::Shield,
+        EquipmentSlot::Ranged,
+        EquipmentSlot::Head,
+        EquipmentSlot::Body,
+        EquipmentSlot::Legs,
+        EquipmentSlot::Feet,
+        EquipmentSlot::Hands,
+        EquipmentSlot::Amulet,
+        EquipmentSlot::Ring,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EquipmentSlot::Melee => "Main Hand",
+            EquipmentSlot::Shield => "Off Hand",
+            EquipmentSlot::Ranged => "Ranged",
+            EquipmentSlot::Head => "Head",
+            EquipmentSlot::Body => "Body",
+            EquipmentSlot::Legs => "Legs",
+            EquipmentSlot::Feet => "Feet",
+            EquipmentSlot::Hands => "Hands",
+            EquipmentSlot::Amulet => "Amulet",
+            EquipmentSlot::Ring => "Ring",
+        }
+    }
+
+    /// How many items can be worn in the slot at once; there is a ring for each hand.
+    pub fn capacity(&self) -> usize {
+        match self {
+            EquipmentSlot::Ring => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum ItemRarity {
+    Common,
+    Magic,
+    Rare,
+}
+
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum BossAbility {
+    Regeneration { amount: i32 },
+    Enrage { bonus: i32, triggered: bool },
 }
 
 // Serialization helper code. We need to implement ConvertSaveload for each type that contains an
@@ -21,6 +81,12 @@ pub struct SerializeMe;
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SerializationHelper {
     pub map: super::map::Map,
+    pub spawned_bosses: super::spawner::SpawnedBosses,
+    pub identification: super::ItemIdentification,
+    #[serde(default)]
+    pub turn_clock: super::initiative_system::TurnClock,
+    #[serde(default)]
+    pub damage_history: super::damage_system::DamageHistory,
 }
 
 #[derive(Component, ConvertSaveload, Clone)]
@@ -28,11 +94,217 @@ pub struct MeleePowerBonus {
     pub power: i32,
 }
 
+/// Equipment that speeds up natural healing, by this many turns between each hit point regained.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct HealthRegenBonus {
+    pub turns: i32,
+}
+
+/// The player is waiting until they're healed or something disturbs them. Remembers their health
+/// so that being hurt wakes them.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Resting {
+    pub hp: i32,
+}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct DefenseBonus {
     pub defense: i32,
 }
 
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug, Default)]
+pub enum DamageType {
+    #[default]
+    Bludgeoning,
+    Slashing,
+    Piercing,
+    Fire,
+    Cold,
+    Poison,
+    Magic,
+    Starvation,
+}
+
+impl DamageType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamageType::Bludgeoning => "bludgeoning",
+            DamageType::Slashing => "slashing",
+            DamageType::Piercing => "piercing",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Poison => "poison",
+            DamageType::Magic => "magic",
+            DamageType::Starvation => "starvation",
+        }
+    }
+}
+
+/// Percentage of each damage type ignored by a creature, or by whoever has the item equipped.
+/// Negative values are vulnerabilities.
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
+pub struct Resistances {
+    pub percent: HashMap<DamageType, i32>,
+}
+
+impl Resistances {
+    pub fn get(&self, damage_type: DamageType) -> i32 {
+        *self.percent.get(&damage_type).unwrap_or(&0)
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct MeleeWeapon {
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_type: DamageType,
+    pub hit_bonus: i32,
+}
+
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum AmmoType {
+    Arrow,
+    Bolt,
+}
+
+impl AmmoType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AmmoType::Arrow => "arrow",
+            AmmoType::Bolt => "bolt",
+        }
+    }
+}
+
+/// Shots beyond half the range take a penalty to hit, and nothing can be hit beyond the range.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct RangedWeapon {
+    pub range: i32,
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_type: DamageType,
+    pub hit_bonus: i32,
+    pub ammo: AmmoType,
+}
+
+/// A stack of projectiles, one of which is used up by every shot.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Ammunition {
+    pub ammo_type: AmmoType,
+    pub count: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NaturalAttack {
+    pub name: String,
+    pub hit_bonus: i32,
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_bonus: i32,
+    pub damage_type: DamageType,
+    pub strikes: i32,
+}
+
+/// Claws, teeth and hide for creatures that don't wield equipment. One attack is chosen each turn
+/// and strikes the given number of times; armor adds to the creature's armor class.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct NaturalAttackDefense {
+    pub armor: i32,
+    pub attacks: Vec<NaturalAttack>,
+}
+
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct BonusDamage {
+    pub damage: i32,
+    pub damage_type: DamageType,
+}
+
+/// Weapons without one only score critical hits on a natural 20, for double damage.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct CriticalHit {
+    pub threat: i32,
+    pub multiplier: i32,
+}
+
+/// Weapons held in both hands, leaving no room for a shield or a second weapon.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct TwoHanded {}
+
+/// Weapons light enough to be wielded in the off hand, for an extra attack.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct OffHand {}
+
+/// Weapons that can strike foes further away than the adjacent tiles.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Reach {
+    pub range: i32,
+}
+
+/// A chance for a weapon to inflict a status effect on whatever it hits.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct WeaponProc {
+    pub kind: StatusKind,
+    pub chance: i32,
+    pub duration: i32,
+    pub magnitude: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Cursed {
+    pub revealed: bool,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct RemovesCurse {}
+
+/// Restores the durability of everything the user has equipped.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct RepairsItems {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Durability {
+    pub max: i32,
+    pub current: i32,
+}
+
+impl Durability {
+    /// Worn equipment loses a point of any bonus it grants for each quarter of its durability
+    /// that is gone. Penalties from curses are left as they are.
+    pub fn worn(&self, bonus: i32) -> i32 {
+        if bonus <= 0 {
+            return bonus;
+        }
+        i32::max(0, bonus - self.penalty())
+    }
+
+    pub fn penalty(&self) -> i32 {
+        (self.max - self.current) * 4 / self.max
+    }
+}
+
+/// Wear queued against an item's durability when its owner hits or is hit.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct SufferWear {
+    pub amount: i32,
+}
+
+impl SufferWear {
+    pub fn new_wear(store: &mut WriteStorage<SufferWear>, item: Entity, amount: i32) {
+        if let Some(wear) = store.get_mut(item) {
+            wear.amount += amount;
+        } else {
+            store
+                .insert(item, SufferWear { amount })
+                .expect("Unable to insert wear");
+        }
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct MagicItem {
+    pub rarity: ItemRarity,
+}
+
 #[derive(Component, ConvertSaveload, Debug, Clone)]
 pub struct WantsToRemoveItem {
     pub item: Entity,
@@ -66,9 +338,88 @@ pub struct Renderable {
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Player {}
 
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum HungerState {
+    WellFed,
+    Normal,
+    Hungry,
+    Starving,
+}
+
+impl HungerState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HungerState::WellFed => "Well Fed",
+            HungerState::Normal => "Normal",
+            HungerState::Hungry => "Hungry",
+            HungerState::Starving => "Starving",
+        }
+    }
+}
+
+/// Counts down the turns until its owner grows hungrier.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct HungerClock {
+    pub state: HungerState,
+    pub duration: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct ProvidesFood {}
+
+/// Not drawn until the player spots it.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Hidden {}
+
+/// Fires its effects on whatever enters its tile.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct EntryTrigger {}
+
+/// Marks entities that changed tiles this turn, so entry triggers can fire.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct EntityMoved {}
+
+/// Removed once it has been triggered.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct SingleActivation {}
+
+/// Sends its victim to a random spot on the level.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct TeleportsTarget {}
+
+/// Pushes whatever it hits this many tiles away from the attacker.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Knockback {
+    pub distance: i32,
+}
+
+/// Drags its target up to this many tiles back towards the user, like a grappling hook.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct PullsTarget {
+    pub distance: i32,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Monster {}
 
+/// Speed is a percentage of normal. Energy builds up by the speed every time slice and an action
+/// is taken whenever enough has been stored.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Energy {
+    pub speed: i32,
+    pub current: i32,
+}
+
+/// Marks monsters with enough energy to act during this time slice.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct MyTurn {}
+
+/// Heavy equipment slows down whoever wears it.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct SpeedPenalty {
+    pub penalty: i32,
+}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Name {
     pub name: String,
@@ -81,13 +432,158 @@ pub struct Viewshed {
     pub dirty: bool,
 }
 
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Boss {
+    pub abilities: Vec<BossAbility>,
+    pub loot: Vec<String>,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct BlocksTile {}
 
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub struct Attribute {
+    pub base: i32,
+    pub modifiers: i32,
+}
+
+impl Attribute {
+    pub fn new(base: i32) -> Self {
+        Self { base, modifiers: 0 }
+    }
+
+    pub fn value(&self) -> i32 {
+        self.base + self.modifiers
+    }
+
+    pub fn bonus(&self) -> i32 {
+        attr_bonus(self.value())
+    }
+}
+
+pub fn attr_bonus(value: i32) -> i32 {
+    (value - 10).div_euclid(2)
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Attributes {
+    pub might: Attribute,
+    pub fitness: Attribute,
+    pub quickness: Attribute,
+    pub intelligence: Attribute,
+}
+
+impl Attributes {
+    pub fn new(might: i32, fitness: i32, quickness: i32, intelligence: i32) -> Self {
+        Self {
+            might: Attribute::new(might),
+            fitness: Attribute::new(fitness),
+            quickness: Attribute::new(quickness),
+            intelligence: Attribute::new(intelligence),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum Skill {
+    Melee,
+    Ranged,
+    Defense,
+    Magic,
+}
+
+impl Skill {
+    pub const ALL: [Skill; 4] = [Skill::Melee, Skill::Ranged, Skill::Defense, Skill::Magic];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skill::Melee => "melee",
+            Skill::Ranged => "ranged",
+            Skill::Defense => "defense",
+            Skill::Magic => "magic",
+        }
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Default, Debug)]
+pub struct Skills {
+    pub ranks: HashMap<Skill, i32>,
+    pub practice: HashMap<Skill, i32>,
+}
+
+impl Skills {
+    pub fn rank(&self, skill: Skill) -> i32 {
+        *self.ranks.get(&skill).unwrap_or(&0)
+    }
+
+    pub fn improve(&mut self, skill: Skill) {
+        *self.ranks.entry(skill).or_insert(0) += 1;
+        self.practice.remove(&skill);
+    }
+
+    /// Records a use of the skill, returning true if enough practice has been done to gain a rank.
+    pub fn practice(&mut self, skill: Skill) -> bool {
+        let needed = (self.rank(skill) + 1) * 10;
+        let practice = self.practice.entry(skill).or_insert(0);
+        *practice += 1;
+        if *practice >= needed {
+            self.improve(skill);
+            return true;
+        }
+        false
+    }
+
+    /// Practices a skill for an entity that may not have any skills, returning the new rank if it
+    /// improved.
+    pub fn practice_skill(
+        store: &mut WriteStorage<Skills>,
+        entity: Entity,
+        skill: Skill,
+    ) -> Option<i32> {
+        let skills = store.get_mut(entity)?;
+        if skills.practice(skill) {
+            Some(skills.rank(skill))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
+}
+
+impl Experience {
+    /// Experience awarded for defeating a creature of this level.
+    pub fn xp_value(&self) -> i32 {
+        self.level * 100
+    }
+
+    /// Experience needed to advance beyond the current level.
+    pub fn xp_to_level(&self) -> i32 {
+        self.level * 500
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct LevelUpFlash {
+    pub lifetime_ms: f32,
+}
+
+/// A purely visual entity that disappears once its time is up.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct ParticleLifetime {
+    pub lifetime_ms: f32,
+}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct CombatStats {
     pub max_hp: i32,
     pub hp: i32,
+    pub max_mana: i32,
+    pub mana: i32,
     pub defense: i32,
     pub power: i32,
 }
@@ -97,24 +593,58 @@ pub struct WantsToMelee {
     pub target: Entity,
 }
 
-#[derive(Component, ConvertSaveload, Debug, Clone)]
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToShoot {
+    pub target: rltk::Point,
+}
+
+/// A single blow waiting to be dealt, with a description of whoever or whatever dealt it.
+#[derive(Debug, Clone)]
+pub struct DamageEntry {
+    pub amount: i32,
+    pub damage_type: DamageType,
+    pub source: String,
+    pub attacker: Option<Entity>,
+}
+
+/// Damage waiting to be dealt. It never outlives the turn, so it isn't saved.
+#[derive(Component, Debug, Clone)]
 pub struct SufferDamage {
-    pub amount: Vec<i32>,
+    pub entries: Vec<DamageEntry>,
 }
 
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(
+        store: &mut WriteStorage<SufferDamage>,
+        victim: Entity,
+        amount: i32,
+        damage_type: DamageType,
+        source: &str,
+        attacker: Option<Entity>,
+    ) {
+        let entry = DamageEntry {
+            amount,
+            damage_type,
+            source: source.to_string(),
+            attacker,
+        };
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
+            suffering.entries.push(entry);
         } else {
             let dmg = SufferDamage {
-                amount: vec![amount],
+                entries: vec![entry],
             };
             store.insert(victim, dmg).expect("Unable to insert damage");
         }
     }
 }
 
+/// Whoever dealt the blow that killed this creature, so they can be credited with the kill.
+#[derive(Component, Debug, Clone)]
+pub struct SlainBy {
+    pub killer: Entity,
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Item {}
 
@@ -150,6 +680,9 @@ pub struct ProvidesHealing {
     pub heal_amount: i32,
 }
 
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ProvidesIdentification {}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct Ranged {
     pub range: i32,
@@ -158,6 +691,7 @@ pub struct Ranged {
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct InflictsDamage {
     pub damage: i32,
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -165,7 +699,186 @@ pub struct AreaOfEffect {
     pub radius: i32,
 }
 
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum StatusKind {
+    Confused,
+    Poisoned,
+    Asleep,
+    Slowed,
+    Hasted,
+    Regenerating,
+    Blinded,
+    Stunned,
+}
+
+impl StatusKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusKind::Confused => "confused",
+            StatusKind::Poisoned => "poisoned",
+            StatusKind::Asleep => "asleep",
+            StatusKind::Slowed => "slowed",
+            StatusKind::Hasted => "hasted",
+            StatusKind::Regenerating => "regenerating",
+            StatusKind::Blinded => "blinded",
+            StatusKind::Stunned => "stunned",
+        }
+    }
+
+    /// Stacking effects add another instance when reapplied, the rest refresh the existing one.
+    pub fn stacks(&self) -> bool {
+        matches!(self, StatusKind::Poisoned)
+    }
+}
+
+/// An effect that lasts for a number of turns. Each one is its own entity, attached to its target.
+/// Whoever inflicted it is credited with any harm it does.
+#[derive(Component, Debug, Clone)]
+pub struct StatusEffect {
+    pub target: Entity,
+    pub kind: StatusKind,
+    pub duration: i32,
+    pub magnitude: i32,
+    pub source: Option<Entity>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StatusEffectData<M> {
+    target: M,
+    kind: StatusKind,
+    duration: i32,
+    magnitude: i32,
+    source: Option<M>,
+}
+
+// Written by hand because the derive can't handle an optional entity. A source that has since
+// been removed from the world simply isn't remembered.
+impl<M: Marker + Serialize> ConvertSaveload<M> for StatusEffect
+where
+    for<'de> M: Deserialize<'de>,
+{
+    type Data = StatusEffectData<M>;
+    type Error = NoError;
+
+    fn convert_into<F>(&self, mut ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        Ok(StatusEffectData {
+            target: ids(self.target).unwrap(),
+            kind: self.kind,
+            duration: self.duration,
+            magnitude: self.magnitude,
+            source: self.source.and_then(&mut ids),
+        })
+    }
+
+    fn convert_from<F>(data: Self::Data, mut ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        Ok(StatusEffect {
+            target: ids(data.target).unwrap(),
+            kind: data.kind,
+            duration: data.duration,
+            magnitude: data.magnitude,
+            source: data.source.and_then(&mut ids),
+        })
+    }
+}
+
+/// Status effects waiting to be attached to an entity by the StatusEffectSystem, along with who
+/// inflicted them. They never outlive the turn, so they aren't saved.
+#[derive(Component, Debug, Clone)]
+pub struct ApplyStatus {
+    pub effects: Vec<(StatusKind, i32, i32, Option<Entity>)>,
+}
+
+impl ApplyStatus {
+    pub fn new_status(
+        store: &mut WriteStorage<ApplyStatus>,
+        victim: Entity,
+        kind: StatusKind,
+        duration: i32,
+        magnitude: i32,
+        source: Option<Entity>,
+    ) {
+        if let Some(pending) = store.get_mut(victim) {
+            pending.effects.push((kind, duration, magnitude, source));
+        } else {
+            let status = ApplyStatus {
+                effects: vec![(kind, duration, magnitude, source)],
+            };
+            store
+                .insert(victim, status)
+                .expect("Unable to insert status");
+        }
+    }
+}
+
+/// A spell the owner can cast for the listed cost. Its effects are described with the same
+/// components as items.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Spell {
+    pub mana_cost: i32,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
-pub struct Confusion {
-    pub turns: i32,
+pub struct InSpellbook {
+    pub owner: Entity,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct TeachesSpell {
+    pub spell: String,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct InflictsStatus {
+    pub kind: StatusKind,
+    pub duration: i32,
+    pub magnitude: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equipment_slot_capacity() {
+        assert_eq!(EquipmentSlot::Ring.capacity(), 2);
+        assert_eq!(EquipmentSlot::Melee.capacity(), 1);
+        assert_eq!(EquipmentSlot::Shield.capacity(), 1);
+    }
+
+    #[test]
+    fn durability_penalty_grows_with_each_quarter_lost() {
+        let at = |current| Durability { current, max: 20 };
+        assert_eq!(at(20).penalty(), 0);
+        assert_eq!(at(16).penalty(), 0);
+        assert_eq!(at(15).penalty(), 1);
+        assert_eq!(at(10).penalty(), 2);
+        assert_eq!(at(1).penalty(), 3);
+        assert_eq!(at(0).penalty(), 4);
+    }
+
+    #[test]
+    fn worn_bonuses_never_turn_into_penalties() {
+        let battered = Durability {
+            current: 5,
+            max: 20,
+        };
+        assert_eq!(battered.worn(4), 1);
+        assert_eq!(battered.worn(2), 0);
+        // Curses are left alone
+        assert_eq!(battered.worn(-2), -2);
+        assert_eq!(
+            Durability {
+                current: 20,
+                max: 20
+            }
+            .worn(2),
+            2
+        );
+    }
 }
@@ -1,19 +1,179 @@
-use super::{CombatStats, GameLog, Name, Player, RunState, SufferDamage};
+use super::{
+    initiative_system::TurnClock, Attributes, Boss, CombatStats, DamageType, Equipped, Experience,
+    GameLog, LevelUpFlash, Name, Player, Position, Resistances, RunState, Skill, Skills, SlainBy,
+    StatusEffect, StatusKind, SufferDamage,
+};
+use rltk::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
+use specs::storage::GenericReadStorage;
+use std::collections::{HashSet, VecDeque};
+
+/// Out of this many, the chance that a slain monster leaves a corpse behind.
+const CORPSE_CHANCE: i32 = 2;
+
+/// How many of the player's most recent injuries are remembered for the death recap.
+pub const RECAP_LENGTH: usize = 8;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DamageEvent {
+    pub source: String,
+    pub amount: i32,
+    pub damage_type: DamageType,
+    pub turn: i32,
+}
+
+/// The damage the player has taken lately, and the blow that finished them off.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct DamageHistory {
+    pub events: VecDeque<DamageEvent>,
+    pub killing_blow: Option<DamageEvent>,
+}
+
+/// The victim's total resistance to a type of damage, as a percentage. Natural resistances stack
+/// with those of any equipped armor.
+pub fn resistance_to(
+    victim: Entity,
+    damage_type: DamageType,
+    entities: &Entities,
+    resistances: &ReadStorage<Resistances>,
+    equipped: &impl GenericReadStorage<Component = Equipped>,
+) -> i32 {
+    let natural = resistances.get(victim).map_or(0, |r| r.get(damage_type));
+    let worn: i32 = (entities, resistances)
+        .join()
+        .filter(|(item, _)| equipped.get(*item).is_some_and(|e| e.owner == victim))
+        .map(|(_, r)| r.get(damage_type))
+        .sum();
+    (natural + worn).clamp(-100, 100)
+}
+
+/// How much of an attack actually lands once resistance is taken into account.
+pub fn damage_taken(amount: i32, resistance: i32) -> i32 {
+    amount - amount * resistance / 100
+}
+
+/// How much of a hit gets through the victim's resistances, adding up every type of damage it
+/// deals.
+pub fn total_damage_taken(
+    victim: Entity,
+    damage: &[(i32, DamageType)],
+    entities: &Entities,
+    resistances: &ReadStorage<Resistances>,
+    equipped: &impl GenericReadStorage<Component = Equipped>,
+) -> i32 {
+    damage
+        .iter()
+        .map(|(amount, damage_type)| {
+            let resistance = resistance_to(victim, *damage_type, entities, resistances, equipped);
+            damage_taken(*amount, resistance)
+        })
+        .sum()
+}
 
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
+        Entities<'a>,
         WriteStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, StatusEffect>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, TurnClock>,
+        WriteExpect<'a, DamageHistory>,
+        WriteStorage<'a, SlainBy>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage) = data;
+        let (
+            entities,
+            mut stats,
+            mut damage,
+            resistances,
+            equipped,
+            names,
+            mut log,
+            statuses,
+            player_entity,
+            clock,
+            mut history,
+            mut slain_by,
+        ) = data;
+
+        for (entity, stats, damage) in (&entities, &mut stats, &damage).join() {
+            // Each kind of damage is only remarked on once, however many blows it came from
+            let mut remarked = HashSet::new();
+            for entry in damage.entries.iter() {
+                let damage_type = &entry.damage_type;
+                let resistance =
+                    resistance_to(entity, *damage_type, &entities, &resistances, &equipped);
+                let taken = damage_taken(entry.amount, resistance);
+                let was_alive = stats.hp > 0;
+                stats.hp -= taken;
+
+                // Credit the kill to whoever landed the final blow
+                if was_alive && stats.hp < 1 {
+                    if let Some(killer) = entry.attacker {
+                        slain_by
+                            .insert(entity, SlainBy { killer })
+                            .expect("Unable to insert killer");
+                    }
+                }
+
+                // Remember what hurt the player, for when they want to know what killed them
+                if entity == *player_entity && taken > 0 {
+                    let event = DamageEvent {
+                        source: entry.source.clone(),
+                        amount: taken,
+                        damage_type: *damage_type,
+                        turn: clock.turn(),
+                    };
+                    if stats.hp < 1 && history.killing_blow.is_none() {
+                        history.killing_blow = Some(event.clone());
+                    }
+                    history.events.push_back(event);
+                    if history.events.len() > RECAP_LENGTH {
+                        history.events.pop_front();
+                    }
+                }
+
+                if !remarked.insert(*damage_type) {
+                    continue;
+                }
+                if let Some(name) = names.get(entity) {
+                    if resistance >= 100 {
+                        log.entries.push(format!(
+                            "{} is unharmed by the {}.",
+                            name.name,
+                            damage_type.name()
+                        ));
+                    } else if resistance < 0 {
+                        log.entries.push(format!(
+                            "{} is vulnerable to {}!",
+                            name.name,
+                            damage_type.name()
+                        ));
+                    }
+                }
+            }
+        }
 
-        for (mut stats, damage) in (&mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
+        // Being hurt wakes the sleeping
+        for (effect, status) in (&entities, &statuses).join() {
+            if status.kind == StatusKind::Asleep && damage.get(status.target).is_some() {
+                entities
+                    .delete(effect)
+                    .expect("Unable to delete status effect");
+                if let Some(name) = names.get(status.target) {
+                    log.entries.push(format!("{} wakes up!", name.name));
+                }
+            }
         }
 
         damage.clear();
@@ -22,13 +182,22 @@ impl<'a> System<'a> for DamageSystem {
 
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
+    let mut loot: Vec<(String, i32, i32)> = Vec::new();
+    let mut corpses: Vec<(String, i32, i32)> = Vec::new();
+    let mut xp_gained = 0;
     // Using a scope to make the borrow checker happy
     {
         let combat_stats = ecs.read_storage::<CombatStats>();
         let players = ecs.read_storage::<Player>();
         let entities = ecs.entities();
         let names = ecs.read_storage::<Name>();
+        let bosses = ecs.read_storage::<Boss>();
+        let positions = ecs.read_storage::<Position>();
+        let experience = ecs.read_storage::<Experience>();
+        let slain_by = ecs.read_storage::<SlainBy>();
+        let player_entity = *ecs.fetch::<Entity>();
         let mut log = ecs.write_resource::<GameLog>();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
         for (entity, stats) in (&entities, &combat_stats).join() {
             if stats.hp < 1 {
                 match players.get(entity) {
@@ -37,6 +206,28 @@ pub fn delete_the_dead(ecs: &mut World) {
                         if let Some(victim_name) = victim_name {
                             log.entries.push(format!("{} is dead", &victim_name.name))
                         }
+                        if let (Some(boss), Some(pos)) = (bosses.get(entity), positions.get(entity))
+                        {
+                            log.entries
+                                .push("Its hoard spills across the floor.".to_string());
+                            for item in boss.loot.iter() {
+                                loot.push((item.clone(), pos.x, pos.y));
+                            }
+                        }
+                        if let (Some(victim_name), Some(pos)) = (victim_name, positions.get(entity))
+                        {
+                            if rng.roll_dice(1, CORPSE_CHANCE) == 1 {
+                                corpses.push((victim_name.name.clone(), pos.x, pos.y));
+                            }
+                        }
+                        // Only the player's own kills are worth experience
+                        let killed_by_player = slain_by
+                            .get(entity)
+                            .is_some_and(|s| s.killer == player_entity);
+                        if let (true, Some(victim_xp)) = (killed_by_player, experience.get(entity))
+                        {
+                            xp_gained += victim_xp.xp_value();
+                        }
                         dead.push(entity);
                     }
                     Some(_) => {
@@ -54,4 +245,86 @@ pub fn delete_the_dead(ecs: &mut World) {
     for victim in dead {
         ecs.delete_entity(victim).expect("Unable to delete");
     }
+
+    if xp_gained > 0 {
+        award_experience(ecs, xp_gained);
+    }
+
+    let depth = ecs.fetch::<super::Map>().depth;
+    for (item, x, y) in loot.iter() {
+        super::spawner::spawn_named(ecs, item, *x, *y, depth);
+    }
+    for (name, x, y) in corpses.iter() {
+        super::spawner::corpse(ecs, *x, *y, name);
+    }
+}
+
+fn award_experience(ecs: &mut World, amount: i32) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut experience = ecs.write_storage::<Experience>();
+    let mut combat_stats = ecs.write_storage::<CombatStats>();
+    let mut attributes = ecs.write_storage::<Attributes>();
+    let mut flashes = ecs.write_storage::<LevelUpFlash>();
+    let mut skills = ecs.write_storage::<Skills>();
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    let mut log = ecs.write_resource::<GameLog>();
+
+    let player_xp = match experience.get_mut(player_entity) {
+        Some(player_xp) => player_xp,
+        None => return,
+    };
+
+    player_xp.xp += amount;
+    while player_xp.xp >= player_xp.xp_to_level() {
+        player_xp.xp -= player_xp.xp_to_level();
+        player_xp.level += 1;
+        log.entries.push(format!(
+            "Congratulations, you are now level {}!",
+            player_xp.level
+        ));
+
+        // Each level improves a random attribute...
+        if let Some(attr) = attributes.get_mut(player_entity) {
+            let (name, improved) = match rng.roll_dice(1, 4) {
+                1 => ("stronger", &mut attr.might),
+                2 => ("healthier", &mut attr.fitness),
+                3 => ("quicker", &mut attr.quickness),
+                _ => ("smarter", &mut attr.intelligence),
+            };
+            improved.base += 1;
+            log.entries.push(format!("You feel {}!", name));
+        }
+
+        // ...grants a rank in a random skill...
+        if let Some(player_skills) = skills.get_mut(player_entity) {
+            let skill = Skill::ALL[(rng.roll_dice(1, Skill::ALL.len() as i32) - 1) as usize];
+            player_skills.improve(skill);
+            log.entries.push(format!(
+                "Your {} skill improves to {}!",
+                skill.name(),
+                player_skills.rank(skill)
+            ));
+        }
+
+        // ...and adds hit points based on fitness and mana based on intelligence, fully
+        // restoring both
+        let (fitness, intelligence) = attributes
+            .get(player_entity)
+            .map_or((0, 0), |a| (a.fitness.bonus(), a.intelligence.bonus()));
+        if let Some(stats) = combat_stats.get_mut(player_entity) {
+            stats.max_hp += i32::max(1, rng.roll_dice(1, 8) + fitness);
+            stats.hp = stats.max_hp;
+            stats.max_mana += i32::max(1, rng.roll_dice(1, 4) + intelligence);
+            stats.mana = stats.max_mana;
+        }
+
+        flashes
+            .insert(
+                player_entity,
+                LevelUpFlash {
+                    lifetime_ms: 1500.0,
+                },
+            )
+            .expect("Unable to insert level up flash");
+    }
 }
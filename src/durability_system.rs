@@ -0,0 +1,92 @@
+use super::{Durability, EquipmentSlot, Equipped, GameLog, MeleeWeapon, Name, SufferWear};
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+/// Out of this many, the chance that a point of wear actually costs durability.
+const WEAR_CHANCE: i32 = 3;
+
+/// The equipment a victim is wearing that blows against them can wear down. Weapons wear from
+/// striking, not from being struck.
+pub fn armor_worn_by(
+    victim: Entity,
+    entities: &Entities,
+    equipped: &ReadStorage<Equipped>,
+    durability: &ReadStorage<Durability>,
+    melee_weapons: &ReadStorage<MeleeWeapon>,
+) -> Vec<Entity> {
+    (entities, equipped, durability)
+        .join()
+        .filter(|(item, equipped_by, _)| {
+            equipped_by.owner == victim
+                && equipped_by.slot != EquipmentSlot::Ranged
+                && melee_weapons.get(*item).is_none()
+        })
+        .map(|(item, _, _)| item)
+        .collect()
+}
+
+/// Landing a blow wears down both the weapon and a random piece of the victim's armor.
+pub fn wear_from_hit(
+    wear: &mut WriteStorage<SufferWear>,
+    rng: &mut RandomNumberGenerator,
+    weapon: Option<Entity>,
+    armor: &[Entity],
+) {
+    if let Some(weapon) = weapon {
+        SufferWear::new_wear(wear, weapon, 1);
+    }
+    if let Some(piece) = rng.random_slice_entry(armor) {
+        SufferWear::new_wear(wear, *piece, 1);
+    }
+}
+
+/// Worn weapons lose their edge, though a hit always does at least a point of damage.
+pub fn blunted(damage: i32, weapon: Entity, durability: &ReadStorage<Durability>) -> i32 {
+    let penalty = durability.get(weapon).map_or(0, |d| d.penalty());
+    i32::max(1, damage - penalty)
+}
+
+/// Wears down equipment from use, destroying anything that runs out of durability.
+pub struct DurabilitySystem {}
+
+impl<'a> System<'a> for DurabilitySystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, SufferWear>,
+        WriteStorage<'a, Durability>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Name>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, player_entity, mut log, mut rng, mut wear, mut durability, equipped, names) =
+            data;
+
+        for (item, wear, durability) in (&entities, &wear, &mut durability).join() {
+            for _ in 0..wear.amount {
+                if rng.roll_dice(1, WEAR_CHANCE) == 1 {
+                    durability.current -= 1;
+                }
+            }
+
+            if durability.current < 1 {
+                let owner = equipped.get(item).map(|e| e.owner);
+                if let (Some(owner), Some(name)) = (owner, names.get(item)) {
+                    if owner == *player_entity {
+                        log.entries.push(format!("Your {} breaks!", name.name));
+                    } else if let Some(owner_name) = names.get(owner) {
+                        log.entries
+                            .push(format!("{}'s {} breaks!", owner_name.name, name.name));
+                    }
+                }
+                entities.delete(item).expect("Unable to delete broken item");
+            }
+        }
+
+        wear.clear();
+    }
+}
@@ -0,0 +1,1336 @@
+use super::{
+    damage_system::{damage_taken, resistance_to},
+    hunger_system::HUNGER_TURNS,
+    initiative_system::ACTION_COST,
+    particle_system::{ParticleBuilder, PARTICLE_LIFETIME_MS},
+    ApplyStatus, CombatStats, Consumable, Cursed, DamageType, Durability, Energy, EntityMoved,
+    EquipmentSlot, Equippable, Equipped, GameLog, HungerClock, HungerState, InBackpack,
+    InflictsDamage, InflictsStatus, ItemIdentification, Knockback, Map, Name, OffHand, Position,
+    ProvidesFood, ProvidesHealing, ProvidesIdentification, PullsTarget, RemovesCurse, RepairsItems,
+    Resistances, Skill, Skills, StatusKind, SufferDamage, TeleportsTarget, TileType, TwoHanded,
+    Viewshed,
+};
+use rltk::{FontCharType, Point, RandomNumberGenerator, RGB};
+use specs::prelude::*;
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug)]
+pub enum EffectType {
+    Damage {
+        amount: i32,
+        damage_type: DamageType,
+    },
+    Healing {
+        amount: i32,
+    },
+    Status {
+        kind: StatusKind,
+        duration: i32,
+        magnitude: i32,
+    },
+    Teleport,
+    Push {
+        distance: i32,
+    },
+    Pull {
+        distance: i32,
+    },
+    /// A wild miss at the target, by whoever caused the effect.
+    Fumble {
+        weapon: Option<Entity>,
+        with_attack: String,
+    },
+    Particle {
+        glyph: FontCharType,
+        fg: RGB,
+        bg: RGB,
+        lifetime: f32,
+    },
+    /// Using an item, which turns into whatever effects its components describe.
+    ItemUse,
+    Equip,
+    Food,
+    Repair,
+    RemoveCurse,
+    Identify,
+}
+
+/// Who an effect lands on. Tiles and areas affect every creature standing in them.
+#[derive(Clone, Debug)]
+pub enum Targets {
+    Single { target: Entity },
+    Tile { tile_idx: usize },
+    Area { tiles: Vec<usize> },
+}
+
+#[derive(Clone, Debug)]
+pub struct EffectSpawner {
+    pub creator: Option<Entity>,
+    pub item: Option<Entity>,
+    pub effect_type: EffectType,
+    pub targets: Targets,
+}
+
+/// Effects waiting to be applied. Anything can queue them, they are all resolved together by
+/// `run_effects_queue`.
+#[derive(Default)]
+pub struct EffectQueue {
+    queue: VecDeque<EffectSpawner>,
+}
+
+impl EffectQueue {
+    pub fn add_effect(
+        &mut self,
+        creator: Option<Entity>,
+        effect_type: EffectType,
+        targets: Targets,
+    ) {
+        self.queue.push_back(EffectSpawner {
+            creator,
+            item: None,
+            effect_type,
+            targets,
+        });
+    }
+
+    /// Queues an effect caused by using an item, so that the user is told what it did.
+    pub fn add_item_effect(
+        &mut self,
+        creator: Entity,
+        item: Entity,
+        effect_type: EffectType,
+        targets: Targets,
+    ) {
+        self.queue.push_back(EffectSpawner {
+            creator: Some(creator),
+            item: Some(item),
+            effect_type,
+            targets,
+        });
+    }
+}
+
+/// Out of 6, the chance that a fumble sends the attacker's weapon flying rather than just
+/// throwing them off balance.
+const DROP_WEAPON_CHANCE: i32 = 2;
+
+/// Each tile a creature still had to travel when it slammed into a wall hurts this much.
+const IMPACT_DIE: i32 = 4;
+
+/// Applies everything queued so far, along with anything that applying it queues up in turn.
+pub fn run_effects_queue(ecs: &mut World) {
+    loop {
+        let effect = ecs.write_resource::<EffectQueue>().queue.pop_front();
+        match effect {
+            Some(effect) => {
+                apply_effect(ecs, &effect);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Applies an effect to everyone it lands on. Returns false if it turned out to be pointless,
+/// so that an item used for it isn't wasted.
+fn apply_effect(ecs: &mut World, effect: &EffectSpawner) -> bool {
+    match &effect.effect_type {
+        // Particles go wherever they're aimed, whether or not anyone is standing there
+        EffectType::Particle { .. } => {
+            spawn_particles(ecs, effect);
+            return true;
+        }
+        EffectType::ItemUse => {
+            use_item(ecs, effect);
+            return true;
+        }
+        _ => {}
+    }
+
+    let victims: Vec<Entity> = {
+        let map = ecs.fetch::<Map>();
+        let combat_stats = ecs.read_storage::<CombatStats>();
+        match &effect.targets {
+            Targets::Single { target } => vec![*target],
+            Targets::Tile { tile_idx } => creatures_in(&map, &combat_stats, &[*tile_idx]),
+            Targets::Area { tiles } => creatures_in(&map, &combat_stats, tiles),
+        }
+    };
+
+    let mut worthwhile = true;
+    for victim in victims {
+        if !ecs.entities().is_alive(victim) {
+            continue;
+        }
+        worthwhile &= match &effect.effect_type {
+            EffectType::Damage {
+                amount,
+                damage_type,
+            } => inflict_damage(ecs, effect, victim, *amount, *damage_type),
+            EffectType::Healing { amount } => heal(ecs, effect, victim, *amount),
+            EffectType::Status {
+                kind,
+                duration,
+                magnitude,
+            } => inflict_status(ecs, effect, victim, *kind, *duration, *magnitude),
+            EffectType::Teleport => teleport(ecs, victim),
+            EffectType::Push { distance } => push(ecs, effect, victim, *distance, false),
+            EffectType::Pull { distance } => push(ecs, effect, victim, *distance, true),
+            EffectType::Fumble {
+                weapon,
+                with_attack,
+            } => fumble(ecs, effect, victim, *weapon, with_attack),
+            EffectType::Equip => equip(ecs, effect, victim),
+            EffectType::Food => eat(ecs, effect, victim),
+            EffectType::Repair => repair(ecs, victim),
+            EffectType::RemoveCurse => remove_curse(ecs, victim),
+            EffectType::Identify => identify_carried(ecs, effect, victim),
+            EffectType::Particle { .. } | EffectType::ItemUse => true,
+        };
+    }
+    worthwhile
+}
+
+/// The item behind an effect, if the player used it and so should hear what it did.
+fn reported_item(ecs: &World, effect: &EffectSpawner) -> Option<String> {
+    if effect.creator != Some(*ecs.fetch::<Entity>()) {
+        return None;
+    }
+    effect
+        .item
+        .and_then(|i| ecs.read_storage::<Name>().get(i).map(|n| n.name.clone()))
+}
+
+fn name_of(ecs: &World, entity: Entity) -> String {
+    ecs.read_storage::<Name>()
+        .get(entity)
+        .map_or("it".to_string(), |n| n.name.clone())
+}
+
+fn log(ecs: &World, message: String) {
+    ecs.fetch_mut::<GameLog>().entries.push(message);
+}
+
+fn spawn_particles(ecs: &mut World, effect: &EffectSpawner) {
+    if let EffectType::Particle {
+        glyph,
+        fg,
+        bg,
+        lifetime,
+    } = effect.effect_type
+    {
+        let map = ecs.fetch::<Map>();
+        let tiles: Vec<(i32, i32)> = match &effect.targets {
+            Targets::Single { target } => ecs
+                .read_storage::<Position>()
+                .get(*target)
+                .map(|p| (p.x, p.y))
+                .into_iter()
+                .collect(),
+            Targets::Tile { tile_idx } => vec![tile_xy(&map, *tile_idx)],
+            Targets::Area { tiles } => tiles.iter().map(|t| tile_xy(&map, *t)).collect(),
+        };
+        let mut particle_builder = ecs.fetch_mut::<ParticleBuilder>();
+        for (x, y) in tiles {
+            particle_builder.request(x, y, fg, bg, glyph, lifetime);
+        }
+    }
+}
+
+/// Turns using an item into the effects its components describe, then uses it up unless none of
+/// them were worth it.
+fn use_item(ecs: &mut World, effect: &EffectSpawner) {
+    let (user, item) = match (effect.creator, effect.item) {
+        (Some(user), Some(item)) => (user, item),
+        _ => return,
+    };
+    let player_entity = *ecs.fetch::<Entity>();
+
+    // Using an item reveals what it is
+    if user == player_entity {
+        let item_name = name_of(ecs, item);
+        let mut identification = ecs.fetch_mut::<ItemIdentification>();
+        let unidentified_name = identification.display_name(&item_name);
+        if identification.identify(&item_name) {
+            log(
+                ecs,
+                format!("The {} was a {}!", unidentified_name, item_name),
+            );
+        }
+    }
+
+    // Only items aimed at others benefit from skill in magic, not what the user drinks
+    let targeted = !matches!(effect.targets, Targets::Single { .. });
+    let is_magic = ecs.read_storage::<InflictsDamage>().contains(item)
+        || (targeted && ecs.read_storage::<InflictsStatus>().contains(item));
+
+    let mut worthwhile = true;
+    for (effect_type, particle) in item_effects(ecs, user, item, targeted) {
+        let item_effect = EffectSpawner {
+            creator: Some(user),
+            item: Some(item),
+            effect_type,
+            targets: effect.targets.clone(),
+        };
+        worthwhile &= apply_effect(ecs, &item_effect);
+        if let Some(particle) = particle {
+            ecs.fetch_mut::<EffectQueue>()
+                .add_effect(Some(user), particle, effect.targets.clone());
+        }
+    }
+
+    // Magic items exercise the user's magic skill; aim is only practiced with bows
+    if is_magic {
+        let rank = Skills::practice_skill(&mut ecs.write_storage::<Skills>(), user, Skill::Magic);
+        if let (Some(rank), true) = (rank, user == player_entity) {
+            log(
+                ecs,
+                format!("Your {} skill improves to {}!", Skill::Magic.name(), rank),
+            );
+        }
+    }
+
+    if worthwhile {
+        let mut consumables = ecs.write_storage::<Consumable>();
+        if let Some(consumable) = consumables.get_mut(item) {
+            if consumable.uses <= 1 {
+                ecs.entities().delete(item).expect("Delete failed");
+            } else {
+                consumable.uses -= 1;
+            }
+        }
+    }
+}
+
+/// What using an item does, going by its components, along with the particle to show wherever
+/// each effect lands. New item behaviours only need a line here and a handler above.
+fn item_effects(
+    ecs: &World,
+    user: Entity,
+    item: Entity,
+    targeted: bool,
+) -> Vec<(EffectType, Option<EffectType>)> {
+    let magic_skill = ecs
+        .read_storage::<Skills>()
+        .get(user)
+        .map_or(0, |s| s.rank(Skill::Magic));
+    let mut effects = Vec::new();
+
+    if ecs.read_storage::<Equippable>().contains(item) {
+        effects.push((EffectType::Equip, None));
+    }
+    if ecs.read_storage::<ProvidesFood>().contains(item) {
+        effects.push((EffectType::Food, None));
+    }
+    if ecs.read_storage::<RepairsItems>().contains(item) {
+        effects.push((EffectType::Repair, None));
+    }
+    if ecs.read_storage::<RemovesCurse>().contains(item) {
+        effects.push((EffectType::RemoveCurse, None));
+    }
+    if ecs.read_storage::<ProvidesIdentification>().contains(item) {
+        effects.push((EffectType::Identify, None));
+    }
+    if let Some(healer) = ecs.read_storage::<ProvidesHealing>().get(item) {
+        effects.push((
+            EffectType::Healing {
+                amount: healer.heal_amount,
+            },
+            Some(particle('♥', rltk::GREEN)),
+        ));
+    }
+    if let Some(status) = ecs.read_storage::<InflictsStatus>().get(item) {
+        effects.push((
+            EffectType::Status {
+                kind: status.kind,
+                duration: status.duration + if targeted { magic_skill } else { 0 },
+                magnitude: status.magnitude,
+            },
+            Some(particle('?', rltk::MAGENTA)),
+        ));
+    }
+    if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(item) {
+        effects.push((
+            EffectType::Damage {
+                amount: damage.damage + magic_skill,
+                damage_type: damage.damage_type,
+            },
+            Some(particle('░', rltk::ORANGE)),
+        ));
+    }
+    if let Some(knockback) = ecs.read_storage::<Knockback>().get(item) {
+        effects.push((
+            EffectType::Push {
+                distance: knockback.distance,
+            },
+            None,
+        ));
+    }
+    if let Some(pull) = ecs.read_storage::<PullsTarget>().get(item) {
+        effects.push((
+            EffectType::Pull {
+                distance: pull.distance,
+            },
+            None,
+        ));
+    }
+    if ecs.read_storage::<TeleportsTarget>().contains(item) {
+        effects.push((EffectType::Teleport, None));
+    }
+
+    effects
+}
+
+fn particle(glyph: char, fg: (u8, u8, u8)) -> EffectType {
+    EffectType::Particle {
+        glyph: rltk::to_cp437(glyph),
+        fg: RGB::named(fg),
+        bg: RGB::named(rltk::BLACK),
+        lifetime: PARTICLE_LIFETIME_MS,
+    }
+}
+
+fn inflict_damage(
+    ecs: &mut World,
+    effect: &EffectSpawner,
+    victim: Entity,
+    amount: i32,
+    damage_type: DamageType,
+) -> bool {
+    // Blame the item if there was one, otherwise whatever caused the effect
+    let source = effect
+        .item
+        .or(effect.creator)
+        .map_or_else(|| damage_type.name().to_string(), |e| name_of(ecs, e));
+    SufferDamage::new_damage(
+        &mut ecs.write_storage::<SufferDamage>(),
+        victim,
+        amount,
+        damage_type,
+        &source,
+        effect.creator,
+    );
+
+    if let Some(item_name) = reported_item(ecs, effect) {
+        // Report what actually gets through the target's resistances
+        let resistance = resistance_to(
+            victim,
+            damage_type,
+            &ecs.entities(),
+            &ecs.read_storage::<Resistances>(),
+            &ecs.read_storage::<Equipped>(),
+        );
+        log(
+            ecs,
+            format!(
+                "You use {} on {}, inflicting {} hp.",
+                item_name,
+                name_of(ecs, victim),
+                damage_taken(amount, resistance)
+            ),
+        );
+    }
+    true
+}
+
+fn heal(ecs: &mut World, effect: &EffectSpawner, victim: Entity, amount: i32) -> bool {
+    if let Some(stats) = ecs.write_storage::<CombatStats>().get_mut(victim) {
+        stats.hp = i32::min(stats.max_hp, stats.hp + amount);
+        if let Some(item_name) = reported_item(ecs, effect) {
+            log(
+                ecs,
+                format!("You use {}, healing {} hp.", item_name, amount),
+            );
+        }
+    }
+    true
+}
+
+fn inflict_status(
+    ecs: &mut World,
+    effect: &EffectSpawner,
+    victim: Entity,
+    kind: StatusKind,
+    duration: i32,
+    magnitude: i32,
+) -> bool {
+    ApplyStatus::new_status(
+        &mut ecs.write_storage::<ApplyStatus>(),
+        victim,
+        kind,
+        duration,
+        magnitude,
+        effect.creator,
+    );
+    if let Some(item_name) = reported_item(ecs, effect) {
+        if Some(victim) != effect.creator {
+            log(
+                ecs,
+                format!(
+                    "You use {} on {}, leaving them {}.",
+                    item_name,
+                    name_of(ecs, victim),
+                    kind.name()
+                ),
+            );
+        }
+    }
+    true
+}
+
+/// Victims land on a random open floor tile.
+fn teleport(ecs: &mut World, victim: Entity) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut map = ecs.fetch_mut::<Map>();
+    let floor: Vec<usize> = (0..map.tiles.len())
+        .filter(|idx| map.tiles[*idx] == TileType::Floor && !map.blocked[*idx])
+        .collect();
+    let destination = match ecs
+        .fetch_mut::<RandomNumberGenerator>()
+        .random_slice_entry(&floor)
+    {
+        Some(idx) => *idx as i32,
+        None => return true,
+    };
+
+    let mut positions = ecs.write_storage::<Position>();
+    let pos = match positions.get_mut(victim) {
+        Some(pos) => pos,
+        None => return true,
+    };
+    let start_idx = map.xy_idx(pos.x, pos.y);
+    pos.x = destination % map.width;
+    pos.y = destination / map.width;
+    relocate(&mut map, victim, start_idx, destination as usize);
+    moved(ecs, victim, (pos.x, pos.y));
+    if victim == player_entity {
+        log(ecs, "The world lurches around you!".to_string());
+    }
+    true
+}
+
+/// Shoves a creature along the line from whoever caused the effect, or drags it towards them.
+fn push(
+    ecs: &mut World,
+    effect: &EffectSpawner,
+    victim: Entity,
+    distance: i32,
+    pull: bool,
+) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut map = ecs.fetch_mut::<Map>();
+    let mut positions = ecs.write_storage::<Position>();
+    let origin = match effect.creator.and_then(|c| positions.get(c)) {
+        Some(origin) => (origin.x, origin.y),
+        None => return true,
+    };
+    let pos = match positions.get_mut(victim) {
+        Some(pos) => pos,
+        None => return true,
+    };
+    let mut step = ((pos.x - origin.0).signum(), (pos.y - origin.1).signum());
+    if step == (0, 0) {
+        return true;
+    }
+    if pull {
+        step = (-step.0, -step.1);
+    }
+
+    let start_idx = map.xy_idx(pos.x, pos.y);
+    let (end, impact) = shove(&map, (pos.x, pos.y), step, distance);
+    pos.x = end.0;
+    pos.y = end.1;
+
+    let end_idx = map.xy_idx(pos.x, pos.y);
+    if end_idx != start_idx {
+        relocate(&mut map, victim, start_idx, end_idx);
+        moved(ecs, victim, end);
+    }
+
+    if let Some(remaining) = impact {
+        let damage = ecs
+            .fetch_mut::<RandomNumberGenerator>()
+            .roll_dice(remaining, IMPACT_DIE);
+        SufferDamage::new_damage(
+            &mut ecs.write_storage::<SufferDamage>(),
+            victim,
+            damage,
+            DamageType::Bludgeoning,
+            "a collision with the wall",
+            effect.creator,
+        );
+        if victim == player_entity || map.visible_tiles[end_idx] {
+            log(
+                ecs,
+                format!("{} slams into the wall!", name_of(ecs, victim)),
+            );
+        }
+    }
+    true
+}
+
+/// A fumble either throws the weapon to the floor or costs the attacker a turn regaining their
+/// balance.
+fn fumble(
+    ecs: &mut World,
+    effect: &EffectSpawner,
+    victim: Entity,
+    weapon: Option<Entity>,
+    with_attack: &str,
+) -> bool {
+    let attacker = match effect.creator {
+        Some(attacker) => attacker,
+        None => return true,
+    };
+    let weapon_dropped = weapon.filter(|w| {
+        ecs.read_storage::<Cursed>().get(*w).is_none()
+            && ecs.fetch_mut::<RandomNumberGenerator>().roll_dice(1, 6) <= DROP_WEAPON_CHANCE
+    });
+    let pos = ecs.read_storage::<Position>().get(attacker).cloned();
+    match weapon_dropped.zip(pos) {
+        Some((dropped, pos)) => {
+            log(
+                ecs,
+                format!(
+                    "{} fumbles an attack on {} and drops the {}!",
+                    name_of(ecs, attacker),
+                    name_of(ecs, victim),
+                    name_of(ecs, dropped)
+                ),
+            );
+            ecs.write_storage::<Equipped>().remove(dropped);
+            ecs.write_storage::<Position>()
+                .insert(dropped, pos)
+                .expect("Unable to drop weapon");
+        }
+        None => {
+            log(
+                ecs,
+                format!(
+                    "{} swings wildly at {}{}, misses completely and stumbles.",
+                    name_of(ecs, attacker),
+                    name_of(ecs, victim),
+                    with_attack
+                ),
+            );
+            if let Some(energy) = ecs.write_storage::<Energy>().get_mut(attacker) {
+                energy.current -= ACTION_COST;
+            }
+        }
+    }
+    true
+}
+
+/// Lets everything that tracks where a creature is know that it has moved.
+fn moved(ecs: &World, victim: Entity, (x, y): (i32, i32)) {
+    if victim == *ecs.fetch::<Entity>() {
+        let mut player_pos = ecs.fetch_mut::<Point>();
+        player_pos.x = x;
+        player_pos.y = y;
+    }
+    if let Some(viewshed) = ecs.write_storage::<Viewshed>().get_mut(victim) {
+        viewshed.dirty = true;
+    }
+    ecs.write_storage::<EntityMoved>()
+        .insert(victim, EntityMoved {})
+        .expect("Unable to insert marker");
+}
+
+/// Equips the item, first unequipping whatever is in the way, unless that is cursed.
+fn equip(ecs: &mut World, effect: &EffectSpawner, wearer: Entity) -> bool {
+    let item = match effect.item {
+        Some(item) => item,
+        None => return true,
+    };
+    let player_entity = *ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let mut equipped = ecs.write_storage::<Equipped>();
+    let mut backpack = ecs.write_storage::<InBackpack>();
+    let mut cursed = ecs.write_storage::<Cursed>();
+    let two_handed = ecs.read_storage::<TwoHanded>();
+    let slot = match ecs.read_storage::<Equippable>().get(item) {
+        Some(can_equip) => can_equip.slot,
+        None => return true,
+    };
+    let item_name = name_of(ecs, item);
+
+    // Light weapons go in the off hand when the main hand already holds a one-handed weapon,
+    // two-handed weapons need both hands free
+    let main_hand = (&entities, &equipped)
+        .join()
+        .find(|(_, e)| e.owner == wearer && e.slot == EquipmentSlot::Melee);
+    let off_hand = slot == EquipmentSlot::Melee
+        && ecs.read_storage::<OffHand>().contains(item)
+        && main_hand.is_some_and(|(main, _)| two_handed.get(main).is_none());
+    let target_slot = if off_hand {
+        EquipmentSlot::Shield
+    } else {
+        slot
+    };
+    let mut needed_slots = vec![target_slot];
+    if two_handed.get(item).is_some() {
+        needed_slots.push(EquipmentSlot::Shield);
+    }
+
+    let worn: Vec<WornItem> = (&entities, &equipped)
+        .join()
+        .filter(|(_, already_equipped)| already_equipped.owner == wearer)
+        .map(|(worn_item, already_equipped)| WornItem {
+            item: worn_item,
+            slot: already_equipped.slot,
+            two_handed: two_handed.get(worn_item).is_some(),
+            cursed: cursed.get(worn_item).is_some(),
+        })
+        .collect();
+    let to_unequip = match make_room(&needed_slots, &worn) {
+        Ok(to_unequip) => to_unequip,
+        Err(stuck_with) => {
+            if wearer == player_entity {
+                log(
+                    ecs,
+                    format!(
+                        "You cannot remove {}, it is cursed!",
+                        name_of(ecs, stuck_with)
+                    ),
+                );
+            }
+            return true;
+        }
+    };
+
+    for worn_item in to_unequip.iter() {
+        equipped.remove(*worn_item);
+        backpack
+            .insert(*worn_item, InBackpack { owner: wearer })
+            .expect("Unable to insert backpack entry");
+        if wearer == player_entity {
+            log(ecs, format!("You unequip {}.", name_of(ecs, *worn_item)));
+        }
+    }
+
+    equipped
+        .insert(
+            item,
+            Equipped {
+                owner: wearer,
+                slot: target_slot,
+            },
+        )
+        .expect("Unable to insert equipped component");
+    backpack.remove(item);
+    if wearer == player_entity {
+        if off_hand {
+            log(ecs, format!("You wield {} in your off hand.", item_name));
+        } else {
+            log(ecs, format!("You equip {}", item_name));
+        }
+    }
+
+    // Curses make themselves known once it is too late
+    if let Some(curse) = cursed.get_mut(item) {
+        if !curse.revealed && wearer == player_entity {
+            log(
+                ecs,
+                format!("The {} is cursed! It binds itself to you.", item_name),
+            );
+        }
+        curse.revealed = true;
+    }
+    true
+}
+
+/// An item someone already has equipped, as far as making room for another is concerned.
+struct WornItem {
+    item: Entity,
+    slot: EquipmentSlot,
+    two_handed: bool,
+    cursed: bool,
+}
+
+/// Works out what has to come off to free up each of the slots needed. When a slot is full, the
+/// first uncursed item in it is removed; if they are all cursed, the cursed item is returned as
+/// the error. A two-handed weapon fills the shield slot as well as its own.
+fn make_room(needed_slots: &[EquipmentSlot], worn: &[WornItem]) -> Result<Vec<Entity>, Entity> {
+    let mut to_unequip: Vec<Entity> = vec![];
+    for slot in needed_slots.iter() {
+        let in_slot: Vec<&WornItem> = worn
+            .iter()
+            .filter(|w| w.slot == *slot || (*slot == EquipmentSlot::Shield && w.two_handed))
+            .collect();
+        let full = in_slot.len() >= slot.capacity() || in_slot.iter().any(|w| w.two_handed);
+        if full && !in_slot.iter().any(|w| to_unequip.contains(&w.item)) {
+            match in_slot.iter().find(|w| !w.cursed) {
+                Some(w) => to_unequip.push(w.item),
+                None => return Err(in_slot[0].item),
+            }
+        }
+    }
+    Ok(to_unequip)
+}
+
+/// Fills the eater up. Creatures that never go hungry have no use for food.
+fn eat(ecs: &mut World, effect: &EffectSpawner, eater: Entity) -> bool {
+    let mut hunger_clocks = ecs.write_storage::<HungerClock>();
+    let hunger = match hunger_clocks.get_mut(eater) {
+        Some(hunger) => hunger,
+        None => return false,
+    };
+    hunger.state = HungerState::WellFed;
+    hunger.duration = HUNGER_TURNS;
+    if let Some(item_name) = reported_item(ecs, effect) {
+        log(ecs, format!("You eat the {}.", item_name));
+    }
+    true
+}
+
+/// Restores everything the user has equipped, which is pointless if nothing is worn.
+fn repair(ecs: &mut World, user: Entity) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut repaired = false;
+    for (item, equipped_by, durability) in (
+        &ecs.entities(),
+        &ecs.read_storage::<Equipped>(),
+        &mut ecs.write_storage::<Durability>(),
+    )
+        .join()
+    {
+        if equipped_by.owner == user && durability.current < durability.max {
+            durability.current = durability.max;
+            repaired = true;
+            if user == player_entity {
+                log(ecs, format!("You repair your {}.", name_of(ecs, item)));
+            }
+        }
+    }
+
+    if !repaired && user == player_entity {
+        log(ecs, "Nothing you have equipped needs repair.".to_string());
+    }
+    repaired
+}
+
+/// Lifts the curses from everything the user has equipped.
+fn remove_curse(ecs: &mut World, user: Entity) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut cursed = ecs.write_storage::<Cursed>();
+    let lifted: Vec<Entity> = (&ecs.entities(), &ecs.read_storage::<Equipped>())
+        .join()
+        .filter(|(item, equipped_by)| equipped_by.owner == user && cursed.contains(*item))
+        .map(|(item, _)| item)
+        .collect();
+
+    for item in lifted.iter() {
+        cursed.remove(*item);
+        if user == player_entity {
+            log(
+                ecs,
+                format!("The curse on your {} is lifted.", name_of(ecs, *item)),
+            );
+        }
+    }
+
+    if lifted.is_empty() && user == player_entity {
+        log(
+            ecs,
+            "You feel as if someone is watching over you.".to_string(),
+        );
+    }
+    true
+}
+
+/// Reveals everything else the user is carrying.
+fn identify_carried(ecs: &mut World, effect: &EffectSpawner, user: Entity) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    let carried: Vec<Entity> = (&ecs.entities(), &ecs.read_storage::<InBackpack>())
+        .join()
+        .filter(|(item, pack)| pack.owner == user && Some(*item) != effect.item)
+        .map(|(item, _)| item)
+        .collect();
+
+    let mut identification = ecs.fetch_mut::<ItemIdentification>();
+    for item in carried.iter() {
+        let item_name = name_of(ecs, *item);
+        let unidentified_name = identification.display_name(&item_name);
+        if identification.identify(&item_name) && user == player_entity {
+            log(
+                ecs,
+                format!("You identify the {} as a {}.", unidentified_name, item_name),
+            );
+        }
+    }
+    true
+}
+
+fn creatures_in(
+    map: &Map,
+    combat_stats: &ReadStorage<CombatStats>,
+    tiles: &[usize],
+) -> Vec<Entity> {
+    tiles
+        .iter()
+        .flat_map(|idx| map.tile_content[*idx].iter())
+        .filter(|e| combat_stats.get(**e).is_some())
+        .copied()
+        .collect()
+}
+
+/// Steps a creature from `start` along `step` for up to `distance` tiles, stopping short of
+/// anything in the way. Returns where it ends up and, if it slammed into a wall, how many tiles of
+/// the shove it had left.
+fn shove(
+    map: &Map,
+    start: (i32, i32),
+    step: (i32, i32),
+    distance: i32,
+) -> ((i32, i32), Option<i32>) {
+    let mut pos = start;
+    let mut remaining = distance;
+    while remaining > 0 {
+        let (x, y) = (pos.0 + step.0, pos.1 + step.1);
+        if x < 0 || x >= map.width || y < 0 || y >= map.height {
+            break;
+        }
+        let idx = map.xy_idx(x, y);
+        if map.tiles[idx] == TileType::Wall {
+            return (pos, Some(remaining));
+        }
+        // Stopped short by whoever is in the way
+        if map.blocked[idx] {
+            break;
+        }
+        pos = (x, y);
+        remaining -= 1;
+    }
+    (pos, None)
+}
+
+/// Moves a creature's footprint on the map so nothing else can be placed on top of it before the
+/// indexing system next runs.
+fn relocate(map: &mut Map, victim: Entity, from: usize, to: usize) {
+    map.blocked[from] = false;
+    map.blocked[to] = true;
+    map.tile_content[from].retain(|e| *e != victim);
+    map.tile_content[to].push(victim);
+}
+
+fn tile_xy(map: &Map, idx: usize) -> (i32, i32) {
+    let idx = idx as i32;
+    (idx % map.width, idx / map.width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single corridor running from (1, 1) to (5, 1), walled in on every side.
+    fn corridor() -> Map {
+        let (width, height) = (7, 3);
+        let count = (width * height) as usize;
+        let mut map = Map {
+            tiles: vec![TileType::Wall; count],
+            rooms: Vec::new(),
+            width,
+            height,
+            revealed_tiles: vec![false; count],
+            visible_tiles: vec![false; count],
+            blocked: vec![false; count],
+            known_traps: vec![false; count],
+            tile_content: vec![Vec::new(); count],
+            depth: 1,
+        };
+        for x in 1..=5 {
+            let idx = map.xy_idx(x, 1);
+            map.tiles[idx] = TileType::Floor;
+        }
+        map
+    }
+
+    #[test]
+    fn shoves_travel_their_full_distance_in_the_open() {
+        let map = corridor();
+        assert_eq!(shove(&map, (1, 1), (1, 0), 3), ((4, 1), None));
+        assert_eq!(shove(&map, (5, 1), (-1, 0), 2), ((3, 1), None));
+    }
+
+    #[test]
+    fn walls_stop_shoves_and_take_the_remaining_distance_as_impact() {
+        let map = corridor();
+        assert_eq!(shove(&map, (3, 1), (1, 0), 5), ((5, 1), Some(3)));
+        // Pinned against the wall already, so the whole shove goes into it
+        assert_eq!(shove(&map, (3, 1), (0, 1), 2), ((3, 1), Some(2)));
+    }
+
+    #[test]
+    fn creatures_in_the_way_stop_shoves_without_impact() {
+        let mut map = corridor();
+        let idx = map.xy_idx(4, 1);
+        map.blocked[idx] = true;
+        assert_eq!(shove(&map, (1, 1), (1, 0), 5), ((3, 1), None));
+    }
+
+    fn worn(item: Entity, slot: EquipmentSlot) -> WornItem {
+        WornItem {
+            item,
+            slot,
+            two_handed: false,
+            cursed: false,
+        }
+    }
+
+    fn items(count: usize) -> Vec<Entity> {
+        let mut world = World::new();
+        (0..count).map(|_| world.create_entity().build()).collect()
+    }
+
+    #[test]
+    fn rings_fill_both_hands_before_one_comes_off() {
+        let rings = items(2);
+
+        let one_ring = [worn(rings[0], EquipmentSlot::Ring)];
+        assert_eq!(make_room(&[EquipmentSlot::Ring], &one_ring), Ok(vec![]));
+
+        let two_rings = [
+            worn(rings[0], EquipmentSlot::Ring),
+            worn(rings[1], EquipmentSlot::Ring),
+        ];
+        assert_eq!(
+            make_room(&[EquipmentSlot::Ring], &two_rings),
+            Ok(vec![rings[0]])
+        );
+    }
+
+    #[test]
+    fn cursed_items_stay_put() {
+        let rings = items(2);
+        let mut two_rings = [
+            worn(rings[0], EquipmentSlot::Ring),
+            worn(rings[1], EquipmentSlot::Ring),
+        ];
+        two_rings[0].cursed = true;
+        assert_eq!(
+            make_room(&[EquipmentSlot::Ring], &two_rings),
+            Ok(vec![rings[1]])
+        );
+
+        two_rings[1].cursed = true;
+        assert_eq!(make_room(&[EquipmentSlot::Ring], &two_rings), Err(rings[0]));
+    }
+
+    #[test]
+    fn two_handed_weapons_need_both_hands() {
+        let gear = items(2);
+        let sword_and_shield = [
+            worn(gear[0], EquipmentSlot::Melee),
+            worn(gear[1], EquipmentSlot::Shield),
+        ];
+        assert_eq!(
+            make_room(
+                &[EquipmentSlot::Melee, EquipmentSlot::Shield],
+                &sword_and_shield
+            ),
+            Ok(vec![gear[0], gear[1]])
+        );
+
+        // A shield pushes out a two-handed weapon, which is only removed once
+        let mut greatsword = worn(gear[0], EquipmentSlot::Melee);
+        greatsword.two_handed = true;
+        assert_eq!(
+            make_room(&[EquipmentSlot::Shield], &[greatsword]),
+            Ok(vec![gear[0]])
+        );
+    }
+
+    /// A world with just enough in it to use items: the player standing in the corridor.
+    fn world() -> (World, Entity) {
+        let mut ecs = World::new();
+        ecs.register::<Name>();
+        ecs.register::<CombatStats>();
+        ecs.register::<Position>();
+        ecs.register::<Viewshed>();
+        ecs.register::<EntityMoved>();
+        ecs.register::<SufferDamage>();
+        ecs.register::<ApplyStatus>();
+        ecs.register::<Resistances>();
+        ecs.register::<Equippable>();
+        ecs.register::<Equipped>();
+        ecs.register::<InBackpack>();
+        ecs.register::<Cursed>();
+        ecs.register::<TwoHanded>();
+        ecs.register::<OffHand>();
+        ecs.register::<HungerClock>();
+        ecs.register::<Durability>();
+        ecs.register::<Consumable>();
+        ecs.register::<Skills>();
+        ecs.register::<InflictsDamage>();
+        ecs.register::<InflictsStatus>();
+        ecs.register::<ProvidesHealing>();
+        ecs.register::<ProvidesFood>();
+        ecs.register::<RepairsItems>();
+        ecs.register::<RemovesCurse>();
+        ecs.register::<ProvidesIdentification>();
+        ecs.register::<TeleportsTarget>();
+        ecs.register::<Knockback>();
+        ecs.register::<PullsTarget>();
+        ecs.register::<Energy>();
+
+        let mut rng = RandomNumberGenerator::seeded(1);
+        ecs.insert(ItemIdentification::new(&mut rng));
+        ecs.insert(rng);
+        ecs.insert(corridor());
+        ecs.insert(Point::new(1, 1));
+        ecs.insert(GameLog {
+            entries: Vec::new(),
+        });
+        ecs.insert(ParticleBuilder::default());
+        ecs.insert(EffectQueue::default());
+
+        let player = ecs
+            .create_entity()
+            .with(Name {
+                name: "Player".to_string(),
+            })
+            .with(Position { x: 1, y: 1 })
+            .with(CombatStats {
+                max_hp: 20,
+                hp: 5,
+                max_mana: 0,
+                mana: 0,
+                defense: 0,
+                power: 0,
+            })
+            .build();
+        ecs.insert(player);
+        (ecs, player)
+    }
+
+    fn carried<'a>(ecs: &'a mut World, owner: Entity, name: &str) -> EntityBuilder<'a> {
+        ecs.create_entity()
+            .with(Name {
+                name: name.to_string(),
+            })
+            .with(InBackpack { owner })
+    }
+
+    fn use_item(ecs: &mut World, user: Entity, item: Entity) {
+        ecs.fetch_mut::<EffectQueue>().add_item_effect(
+            user,
+            item,
+            EffectType::ItemUse,
+            Targets::Single { target: user },
+        );
+        run_effects_queue(ecs);
+        ecs.maintain();
+    }
+
+    #[test]
+    fn potions_heal_and_are_used_up() {
+        let (mut ecs, player) = world();
+        let potion = carried(&mut ecs, player, "Health Potion")
+            .with(Consumable { uses: 1 })
+            .with(ProvidesHealing { heal_amount: 8 })
+            .build();
+
+        use_item(&mut ecs, player, potion);
+
+        assert_eq!(
+            ecs.read_storage::<CombatStats>().get(player).unwrap().hp,
+            13
+        );
+        assert!(!ecs.is_alive(potion));
+        assert!(ecs
+            .fetch::<GameLog>()
+            .entries
+            .contains(&"You use Health Potion, healing 8 hp.".to_string()));
+    }
+
+    #[test]
+    fn equipping_swaps_out_whatever_is_in_the_slot() {
+        let (mut ecs, player) = world();
+        let dagger = ecs
+            .create_entity()
+            .with(Equipped {
+                owner: player,
+                slot: EquipmentSlot::Melee,
+            })
+            .build();
+        let sword = carried(&mut ecs, player, "Longsword")
+            .with(Equippable {
+                slot: EquipmentSlot::Melee,
+            })
+            .build();
+
+        use_item(&mut ecs, player, sword);
+
+        assert!(ecs.read_storage::<Equipped>().get(sword).is_some());
+        assert!(ecs.read_storage::<InBackpack>().get(sword).is_none());
+        assert!(ecs.read_storage::<Equipped>().get(dagger).is_none());
+        assert!(ecs.read_storage::<InBackpack>().get(dagger).is_some());
+    }
+
+    #[test]
+    fn items_that_do_nothing_are_not_used_up() {
+        let (mut ecs, player) = world();
+        let ration = carried(&mut ecs, player, "Ration")
+            .with(Consumable { uses: 1 })
+            .with(ProvidesFood {})
+            .build();
+        let kit = carried(&mut ecs, player, "Repair Kit")
+            .with(Consumable { uses: 1 })
+            .with(RepairsItems {})
+            .build();
+
+        // The player has no hunger clock here, and nothing equipped to repair
+        use_item(&mut ecs, player, ration);
+        use_item(&mut ecs, player, kit);
+
+        assert!(ecs.is_alive(ration));
+        assert!(ecs.is_alive(kit));
+    }
+
+    #[test]
+    fn scrolls_lift_curses_and_identify_what_is_carried() {
+        let (mut ecs, player) = world();
+        let ring = ecs
+            .create_entity()
+            .with(Name {
+                name: "Ring".to_string(),
+            })
+            .with(Equipped {
+                owner: player,
+                slot: EquipmentSlot::Ring,
+            })
+            .with(Cursed { revealed: true })
+            .build();
+        carried(&mut ecs, player, "Health Potion").build();
+        let remove_curse = carried(&mut ecs, player, "Remove Curse Scroll")
+            .with(Consumable { uses: 1 })
+            .with(RemovesCurse {})
+            .build();
+        let identify = carried(&mut ecs, player, "Identify Scroll")
+            .with(Consumable { uses: 1 })
+            .with(ProvidesIdentification {})
+            .build();
+
+        use_item(&mut ecs, player, remove_curse);
+        use_item(&mut ecs, player, identify);
+
+        assert!(ecs.read_storage::<Cursed>().get(ring).is_none());
+        assert_eq!(
+            ecs.fetch::<ItemIdentification>()
+                .display_name("Health Potion"),
+            "Health Potion"
+        );
+        assert!(!ecs.is_alive(remove_curse));
+        assert!(!ecs.is_alive(identify));
+    }
+
+    #[test]
+    fn teleports_move_the_victim_on_the_map() {
+        let (mut ecs, player) = world();
+        ecs.fetch_mut::<Map>().blocked[8] = true;
+        ecs.fetch_mut::<Map>().tile_content[8].push(player);
+
+        ecs.fetch_mut::<EffectQueue>().add_effect(
+            None,
+            EffectType::Teleport,
+            Targets::Single { target: player },
+        );
+        run_effects_queue(&mut ecs);
+
+        let map = ecs.fetch::<Map>();
+        let pos = ecs.read_storage::<Position>().get(player).unwrap().clone();
+        let idx = map.xy_idx(pos.x, pos.y);
+        assert!(map.blocked[idx]);
+        assert!(map.tile_content[idx].contains(&player));
+        assert_eq!(*ecs.fetch::<Point>(), Point::new(pos.x, pos.y));
+        assert!(ecs.read_storage::<EntityMoved>().get(player).is_some());
+    }
+
+    #[test]
+    fn knockback_items_push_their_target_into_the_wall() {
+        let (mut ecs, player) = world();
+        let orc = ecs
+            .create_entity()
+            .with(Name {
+                name: "Orc".to_string(),
+            })
+            .with(Position { x: 3, y: 1 })
+            .with(CombatStats {
+                max_hp: 10,
+                hp: 10,
+                max_mana: 0,
+                mana: 0,
+                defense: 0,
+                power: 0,
+            })
+            .build();
+        let orc_idx = ecs.fetch::<Map>().xy_idx(3, 1);
+        ecs.fetch_mut::<Map>().tile_content[orc_idx].push(orc);
+        let bolt = carried(&mut ecs, player, "Force Bolt")
+            .with(Knockback { distance: 5 })
+            .build();
+
+        ecs.fetch_mut::<EffectQueue>().add_item_effect(
+            player,
+            bolt,
+            EffectType::ItemUse,
+            Targets::Tile { tile_idx: orc_idx },
+        );
+        run_effects_queue(&mut ecs);
+
+        let pos = ecs.read_storage::<Position>().get(orc).unwrap().clone();
+        assert_eq!((pos.x, pos.y), (5, 1));
+        assert!(ecs.read_storage::<SufferDamage>().get(orc).is_some());
+    }
+
+    #[test]
+    fn fumbling_with_a_cursed_weapon_costs_a_turn_instead() {
+        let (mut ecs, player) = world();
+        ecs.write_storage::<Energy>()
+            .insert(
+                player,
+                Energy {
+                    speed: 10,
+                    current: 0,
+                },
+            )
+            .expect("Unable to insert energy");
+        let orc = ecs
+            .create_entity()
+            .with(Name {
+                name: "Orc".to_string(),
+            })
+            .build();
+        let sword = ecs
+            .create_entity()
+            .with(Name {
+                name: "Longsword".to_string(),
+            })
+            .with(Equipped {
+                owner: player,
+                slot: EquipmentSlot::Melee,
+            })
+            .with(Cursed { revealed: false })
+            .build();
+
+        ecs.fetch_mut::<EffectQueue>().add_effect(
+            Some(player),
+            EffectType::Fumble {
+                weapon: Some(sword),
+                with_attack: String::new(),
+            },
+            Targets::Single { target: orc },
+        );
+        run_effects_queue(&mut ecs);
+
+        assert!(ecs.read_storage::<Equipped>().get(sword).is_some());
+        assert!(ecs.read_storage::<Position>().get(sword).is_none());
+        assert_eq!(
+            ecs.read_storage::<Energy>().get(player).unwrap().current,
+            -ACTION_COST
+        );
+        assert!(ecs
+            .fetch::<GameLog>()
+            .entries
+            .contains(&"Player swings wildly at Orc, misses completely and stumbles.".to_string()));
+    }
+}
@@ -1,6 +1,10 @@
 use super::{
-    CombatStats, Consumable, Equipped, GameLog, InBackpack, Map, Name, Player, Position, RunState,
-    State, Viewshed, MAPHEIGHT, MAPWIDTH,
+    damage_system::DamageHistory, initiative_system::TurnClock,
+    regeneration_system::health_regen_interval, spell_system::known_spells, Ammunition, Attributes,
+    CombatStats, Consumable, Cursed, Durability, EquipmentSlot, Equipped, Experience, GameLog,
+    HealthRegenBonus, Hidden, HungerClock, HungerState, InBackpack, ItemIdentification,
+    LevelUpFlash, Map, Name, Player, Position, RunState, Skill, Skills, Spell, State, StatusEffect,
+    StatusKind, TwoHanded, Viewshed, MAPHEIGHT, MAPWIDTH,
 };
 use rltk::{Point, Rltk, VirtualKeyCode, RGB};
 use specs::prelude::*;
@@ -35,28 +39,65 @@ pub enum GameOverResult {
     QuitToMenu,
 }
 
-pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
+pub fn game_over(ecs: &World, ctx: &mut Rltk) -> GameOverResult {
+    let history = ecs.fetch::<DamageHistory>();
+    let depth = ecs.fetch::<Map>().depth;
+    let turns = ecs.fetch::<TurnClock>().turn();
+
     ctx.print_color_centered(
-        15,
+        10,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
         "Your journey has ended!",
     );
+
+    let cause = match &history.killing_blow {
+        Some(blow) => format!(
+            "You were slain by {}, taking {} {} damage.",
+            blow.source,
+            blow.amount,
+            blow.damage_type.name()
+        ),
+        None => "You died.".to_string(),
+    };
+    ctx.print_color_centered(12, RGB::named(rltk::RED), RGB::named(rltk::BLACK), cause);
     ctx.print_color_centered(
-        17,
-        RGB::named(rltk::WHITE),
-        RGB::named(rltk::BLACK),
-        "One day, we'll tell you all about how you did.",
-    );
-    ctx.print_color_centered(
-        18,
+        13,
         RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
-        "That day, sadly, is not in this chapter..",
+        format!("You reached depth {} and survived {} turns.", depth, turns),
     );
 
+    // The last few blows, oldest first
+    let mut y = 15;
+    if !history.events.is_empty() {
+        ctx.print_color_centered(
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            "Your final moments:",
+        );
+        y += 1;
+        for event in history.events.iter() {
+            ctx.print_color_centered(
+                y,
+                RGB::named(rltk::GREY),
+                RGB::named(rltk::BLACK),
+                format!(
+                    "Turn {}: {} {} damage from {}",
+                    event.turn,
+                    event.amount,
+                    event.damage_type.name(),
+                    event.source
+                ),
+            );
+            y += 1;
+        }
+        y += 1;
+    }
+
     ctx.print_color_centered(
-        20,
+        y,
         RGB::named(rltk::MAGENTA),
         RGB::named(rltk::BLACK),
         "Press any key to return to the menu.",
@@ -68,18 +109,26 @@ pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
     }
 }
 
+/// How worn a piece of equipment is, to follow its name in menus.
+fn condition(durability: &Durability) -> String {
+    format!(" [{}/{}]", durability.current, durability.max)
+}
+
 pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
+    let identification = gs.ecs.fetch::<ItemIdentification>();
     let backpack = gs.ecs.read_storage::<Equipped>();
     let entities = gs.ecs.entities();
+    let cursed = gs.ecs.read_storage::<Cursed>();
+    let durability = gs.ecs.read_storage::<Durability>();
 
     let inventory = (&backpack, &names)
         .join()
         .filter(|(item, _)| item.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
@@ -104,11 +153,12 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|(_, item, _)| item.owner == *player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         ctx.set(
             17,
             y,
@@ -131,10 +181,21 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
+        let mut item_name = identification.display_name(&name.name);
+        if let Some(durability) = durability.get(entity) {
+            item_name.push_str(&condition(durability));
+        }
+        ctx.print(21, y, &item_name);
+        if cursed.get(entity).is_some() {
+            ctx.print_color(
+                22 + item_name.len() as i32,
+                y,
+                RGB::named(rltk::RED),
+                RGB::named(rltk::BLACK),
+                "(cursed)",
+            );
+        }
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
     match ctx.key {
@@ -144,10 +205,16 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
             _ => {
                 let selection = rltk::letter_to_option(key);
                 if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
+                    let item = equippable[selection as usize];
+                    if cursed.get(item).is_some() {
+                        let mut gamelog = gs.ecs.fetch_mut::<GameLog>();
+                        gamelog.entries.push(format!(
+                            "You cannot remove {}, it is cursed!",
+                            names.get(item).unwrap().name
+                        ));
+                        return (ItemMenuResult::Cancel, None);
+                    }
+                    return (ItemMenuResult::Selected, Some(item));
                 }
                 (ItemMenuResult::NoResponse, None)
             }
@@ -155,6 +222,158 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
     }
 }
 
+pub fn character_sheet(gs: &mut State, ctx: &mut Rltk) -> ItemMenuResult {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let combat_stats = gs.ecs.read_storage::<CombatStats>();
+    let attributes = gs.ecs.read_storage::<Attributes>();
+    let experience = gs.ecs.read_storage::<Experience>();
+    let skills = gs.ecs.read_storage::<Skills>();
+
+    let white = RGB::named(rltk::WHITE);
+    let yellow = RGB::named(rltk::YELLOW);
+    let black = RGB::named(rltk::BLACK);
+
+    ctx.draw_box(15, 12, 31, 22, white, black);
+    ctx.print_color(18, 12, yellow, black, "Character");
+    ctx.print_color(18, 34, yellow, black, "ESCAPE to close");
+
+    let mut y = 14;
+    if let Some(xp) = experience.get(*player_entity) {
+        ctx.print(
+            17,
+            y,
+            format!("Level {}  XP: {} / {}", xp.level, xp.xp, xp.xp_to_level()),
+        );
+        y += 1;
+    }
+    if let Some(stats) = combat_stats.get(*player_entity) {
+        ctx.print(17, y, format!("HP: {} / {}", stats.hp, stats.max_hp));
+        y += 1;
+        ctx.print(17, y, format!("MP: {} / {}", stats.mana, stats.max_mana));
+        y += 1;
+        let regen = match health_regen_interval(
+            *player_entity,
+            attributes.get(*player_entity),
+            gs.ecs.read_storage::<HungerClock>().get(*player_entity),
+            &gs.ecs.read_storage::<Equipped>(),
+            &gs.ecs.read_storage::<HealthRegenBonus>(),
+        ) {
+            Some(1) => "1 HP / turn".to_string(),
+            Some(turns) => format!("1 HP / {} turns", turns),
+            None => "none (hungry)".to_string(),
+        };
+        ctx.print(17, y, format!("Regen: {}", regen));
+        y += 1;
+    }
+
+    if let Some(attr) = attributes.get(*player_entity) {
+        y += 1;
+        ctx.print_color(17, y, yellow, black, "Attributes");
+        y += 1;
+        for (name, attribute) in [
+            ("Might", &attr.might),
+            ("Fitness", &attr.fitness),
+            ("Quickness", &attr.quickness),
+            ("Intelligence", &attr.intelligence),
+        ] {
+            ctx.print(17, y, name);
+            ctx.print(
+                32,
+                y,
+                format!("{:>2} ({:+})", attribute.value(), attribute.bonus()),
+            );
+            y += 1;
+        }
+    }
+
+    if let Some(player_skills) = skills.get(*player_entity) {
+        y += 1;
+        ctx.print_color(17, y, yellow, black, "Skills");
+        y += 1;
+        for skill in Skill::ALL.iter() {
+            ctx.print(17, y, skill.name());
+            ctx.print(32, y, format!("{:>2}", player_skills.rank(*skill)));
+            y += 1;
+        }
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) | Some(VirtualKeyCode::C) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse,
+    }
+}
+
+pub fn equipment_screen(gs: &mut State, ctx: &mut Rltk) -> ItemMenuResult {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let entities = gs.ecs.entities();
+    let names = gs.ecs.read_storage::<Name>();
+    let equipped = gs.ecs.read_storage::<Equipped>();
+    let cursed = gs.ecs.read_storage::<Cursed>();
+    let two_handed = gs.ecs.read_storage::<TwoHanded>();
+    let durability = gs.ecs.read_storage::<Durability>();
+    let identification = gs.ecs.fetch::<ItemIdentification>();
+
+    let white = RGB::named(rltk::WHITE);
+    let yellow = RGB::named(rltk::YELLOW);
+    let black = RGB::named(rltk::BLACK);
+
+    let both_hands = (&equipped, &two_handed)
+        .join()
+        .any(|(e, _)| e.owner == *player_entity);
+
+    let rows = EquipmentSlot::ALL
+        .iter()
+        .map(|s| s.capacity())
+        .sum::<usize>() as i32;
+    ctx.draw_box(15, 12, 45, rows + 3, white, black);
+    ctx.print_color(18, 12, yellow, black, "Equipment");
+    ctx.print_color(18, 15 + rows, yellow, black, "ESCAPE to close");
+
+    let mut y = 14;
+    for slot in EquipmentSlot::ALL.iter() {
+        let mut worn = (&entities, &equipped, &names)
+            .join()
+            .filter(|(_, e, _)| e.owner == *player_entity && e.slot == *slot);
+
+        for i in 0..slot.capacity() {
+            let label = if slot.capacity() > 1 {
+                format!("{} {}", slot.name(), i + 1)
+            } else {
+                slot.name().to_string()
+            };
+            ctx.print_color(17, y, yellow, black, label);
+            match worn.next() {
+                Some((entity, _, name)) => {
+                    let mut item_name = identification.display_name(&name.name);
+                    if let Some(durability) = durability.get(entity) {
+                        item_name.push_str(&condition(durability));
+                    }
+                    ctx.print(30, y, &item_name);
+                    if cursed.get(entity).is_some() {
+                        ctx.print_color(
+                            31 + item_name.len() as i32,
+                            y,
+                            RGB::named(rltk::RED),
+                            black,
+                            "(cursed)",
+                        );
+                    }
+                }
+                None if both_hands && *slot == EquipmentSlot::Shield => {
+                    ctx.print_color(30, y, RGB::named(rltk::GREY), black, "(both hands)")
+                }
+                None => ctx.print(30, y, "-"),
+            }
+            y += 1;
+        }
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) | Some(VirtualKeyCode::E) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse,
+    }
+}
+
 pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
     let runstate = gs.ecs.fetch::<RunState>();
     let save_exists = super::saveload_system::does_save_exist();
@@ -388,15 +607,17 @@ pub fn ranged_target(
 pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
+    let identification = gs.ecs.fetch::<ItemIdentification>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
     let entities = gs.ecs.entities();
+    let durability = gs.ecs.read_storage::<Durability>();
 
     let inventory = (&backpack, &names)
         .join()
         .filter(|(item, _)| item.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
@@ -421,11 +642,12 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|(_, item, _)| item.owner == *player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         ctx.set(
             17,
             y,
@@ -448,10 +670,12 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
+        let mut item_name = identification.display_name(&name.name);
+        if let Some(durability) = durability.get(entity) {
+            item_name.push_str(&condition(durability));
+        }
+        ctx.print(21, y, item_name);
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
     match ctx.key {
@@ -472,19 +696,108 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     }
 }
 
+fn number_to_option(key: VirtualKeyCode) -> Option<usize> {
+    match key {
+        VirtualKeyCode::Key1 => Some(0),
+        VirtualKeyCode::Key2 => Some(1),
+        VirtualKeyCode::Key3 => Some(2),
+        VirtualKeyCode::Key4 => Some(3),
+        VirtualKeyCode::Key5 => Some(4),
+        VirtualKeyCode::Key6 => Some(5),
+        VirtualKeyCode::Key7 => Some(6),
+        VirtualKeyCode::Key8 => Some(7),
+        VirtualKeyCode::Key9 => Some(8),
+        _ => None,
+    }
+}
+
+/// Spells are listed under the number keys that also cast them outside of the menu.
+pub fn show_spells(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let known = known_spells(&gs.ecs, player_entity);
+    let names = gs.ecs.read_storage::<Name>();
+    let spells = gs.ecs.read_storage::<Spell>();
+    let mana = gs
+        .ecs
+        .read_storage::<CombatStats>()
+        .get(player_entity)
+        .map_or(0, |s| s.mana);
+    let count = known.len();
+
+    let y = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        15,
+        y - 2,
+        34,
+        (count + 3) as i32,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        y - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Cast Which Spell?",
+    );
+    ctx.print_color(
+        18,
+        y + count as i32 + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESC to cancel",
+    );
+
+    for (j, spell) in known.iter().enumerate() {
+        let y = y + j as i32;
+        let cost = spells.get(*spell).map_or(0, |s| s.mana_cost);
+        let fg = if cost <= mana {
+            RGB::named(rltk::WHITE)
+        } else {
+            RGB::named(rltk::GREY)
+        };
+
+        ctx.print_color(
+            17,
+            y,
+            fg,
+            RGB::named(rltk::BLACK),
+            format!(
+                "({}) {} [{} mana]",
+                j + 1,
+                names.get(*spell).unwrap().name,
+                cost
+            ),
+        );
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(VirtualKeyCode::Escape) => (ItemMenuResult::Cancel, None),
+        Some(key) => match number_to_option(key).and_then(|i| known.get(i)) {
+            Some(spell) => (ItemMenuResult::Selected, Some(*spell)),
+            None => (ItemMenuResult::NoResponse, None),
+        },
+    }
+}
+
 pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
+    let identification = gs.ecs.fetch::<ItemIdentification>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
     let entities = gs.ecs.entities();
     let consumables = gs.ecs.read_storage::<Consumable>();
+    let ammunition = gs.ecs.read_storage::<Ammunition>();
+    let cursed = gs.ecs.read_storage::<Cursed>();
+    let durability = gs.ecs.read_storage::<Durability>();
 
     let inventory = (&backpack, &names)
         .join()
         .filter(|(item, _)| item.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
@@ -509,15 +822,19 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     );
 
     let mut equippable = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|(_, item, _)| item.owner == *player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         let mut uses = 1;
         if let Some(consumable) = consumables.get(entity) {
             uses = consumable.uses;
         }
+        if let Some(ammo) = ammunition.get(entity) {
+            uses = ammo.count;
+        }
 
         ctx.set(
             17,
@@ -540,15 +857,21 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
             RGB::named(rltk::BLACK),
             rltk::to_cp437(')'),
         );
-        let mut item_decription = name.name.to_string();
+        let mut item_decription = identification.display_name(&name.name);
         if uses > 1 {
             item_decription.push_str(&format!(" ({})", uses))
         }
+        if let Some(durability) = durability.get(entity) {
+            item_decription.push_str(&condition(durability));
+        }
+        if let Some(curse) = cursed.get(entity) {
+            if curse.revealed {
+                item_decription.push_str(" (cursed)");
+            }
+        }
         ctx.print(21, y, &item_decription);
 
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
     match ctx.key {
@@ -569,6 +892,19 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     }
 }
 
+fn status_colour(kind: StatusKind) -> RGB {
+    match kind {
+        StatusKind::Confused => RGB::named(rltk::MAGENTA),
+        StatusKind::Poisoned => RGB::named(rltk::GREEN),
+        StatusKind::Asleep => RGB::named(rltk::LIGHT_BLUE),
+        StatusKind::Slowed => RGB::named(rltk::GREY),
+        StatusKind::Hasted => RGB::named(rltk::YELLOW),
+        StatusKind::Regenerating => RGB::named(rltk::PINK),
+        StatusKind::Blinded => RGB::named(rltk::DARK_GREY),
+        StatusKind::Stunned => RGB::named(rltk::ORANGE),
+    }
+}
+
 pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
     ctx.draw_box(
         0,
@@ -604,32 +940,139 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         ctx.draw_bar_horizontal(
             28,
             MAPHEIGHT,
-            51,
+            20,
             stats.hp,
             stats.max_hp,
             RGB::named(rltk::RED),
             RGB::named(rltk::BLACK),
         );
+
+        let mana = format!(" MP: {} / {} ", stats.mana, stats.max_mana);
+        ctx.print_color(
+            49,
+            MAPHEIGHT,
+            RGB::named(rltk::CYAN),
+            RGB::named(rltk::BLACK),
+            &mana,
+        );
+
+        ctx.draw_bar_horizontal(
+            63,
+            MAPHEIGHT,
+            16,
+            stats.mana,
+            stats.max_mana,
+            RGB::named(rltk::BLUE),
+            RGB::named(rltk::BLACK),
+        );
     }
 
-    let log = ecs.fetch::<GameLog>();
-    let mut y = MAPHEIGHT + 1;
+    let experience = ecs.read_storage::<Experience>();
+    for (_player, player_xp) in (&players, &experience).join() {
+        let level = format!(
+            " Level {}  XP: {} / {} ",
+            player_xp.level,
+            player_xp.xp,
+            player_xp.xp_to_level()
+        );
+        ctx.print_color(
+            2,
+            MAPHEIGHT + 6,
+            RGB::named(rltk::GOLD),
+            RGB::named(rltk::BLACK),
+            &level,
+        );
+    }
+
+    // Active status effects, with the turns each has left
+    let player_entity = ecs.fetch::<Entity>();
+    let statuses = ecs.read_storage::<StatusEffect>();
+    let mut afflictions: Vec<(StatusKind, i32)> = Vec::new();
+    for status in statuses.join().filter(|s| s.target == *player_entity) {
+        match afflictions
+            .iter_mut()
+            .find(|(kind, _)| *kind == status.kind)
+        {
+            Some((_, turns)) => *turns = i32::max(*turns, status.duration),
+            None => afflictions.push((status.kind, status.duration)),
+        }
+    }
+    let mut x = 28;
+
+    // Hunger only shows once it's something to think about, or while well fed
+    let hunger_clocks = ecs.read_storage::<HungerClock>();
+    if let Some(hunger) = hunger_clocks.get(*player_entity) {
+        let colour = match hunger.state {
+            HungerState::WellFed => Some(RGB::named(rltk::GREEN)),
+            HungerState::Normal => None,
+            HungerState::Hungry => Some(RGB::named(rltk::ORANGE)),
+            HungerState::Starving => Some(RGB::named(rltk::RED)),
+        };
+        if let Some(colour) = colour {
+            let label = format!(" {} ", hunger.state.name());
+            ctx.print_color(x, MAPHEIGHT + 6, colour, RGB::named(rltk::BLACK), &label);
+            x += label.len() as i32;
+        }
+    }
 
-    for s in log.entries.iter().rev().take(LOG_LINES) {
+    for (kind, turns) in afflictions.iter() {
+        let label = format!(" {} {} ", kind.name(), turns);
+        ctx.print_color(
+            x,
+            MAPHEIGHT + 6,
+            status_colour(*kind),
+            RGB::named(rltk::BLACK),
+            &label,
+        );
+        x += label.len() as i32;
+    }
+
+    let log = ecs.fetch::<GameLog>();
+    for (y, s) in (MAPHEIGHT + 1..).zip(log.entries.iter().rev().take(LOG_LINES)) {
         ctx.print(2, y, s);
-        y += 1;
     }
 
+    draw_level_up(ecs, ctx);
+
     let mouse_pos = ctx.mouse_pos();
     ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::MAGENTA));
 
     draw_tooltips(ecs, ctx);
 }
 
+fn draw_level_up(ecs: &World, ctx: &mut Rltk) {
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let mut flashes = ecs.write_storage::<LevelUpFlash>();
+
+    let mut finished = Vec::new();
+    for (entity, pos, flash) in (&entities, &positions, &mut flashes).join() {
+        ctx.set_bg(pos.x, pos.y, RGB::named(rltk::GOLD));
+        ctx.print_color(
+            pos.x - 4,
+            pos.y - 1,
+            RGB::named(rltk::GOLD),
+            RGB::named(rltk::BLACK),
+            "LEVEL UP!",
+        );
+
+        flash.lifetime_ms -= ctx.frame_time_ms;
+        if flash.lifetime_ms < 0.0 {
+            finished.push(entity);
+        }
+    }
+
+    for entity in finished {
+        flashes.remove(entity);
+    }
+}
+
 fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let map = ecs.fetch::<Map>();
     let names = ecs.read_storage::<Name>();
     let positions = ecs.read_storage::<Position>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let identification = ecs.fetch::<ItemIdentification>();
     let mouse_pos = ctx.mouse_pos();
 
     if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height {
@@ -637,10 +1080,10 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     }
 
     let mut tooltip = Vec::new();
-    for (name, position) in (&names, &positions).join() {
+    for (name, position, _) in (&names, &positions, !&hidden).join() {
         let idx = map.xy_idx(position.x, position.y);
         if position.x == mouse_pos.0 && position.y == mouse_pos.1 && map.visible_tiles[idx] {
-            tooltip.push(name.name.to_string());
+            tooltip.push(identification.display_name(&name.name));
         }
     }
 
@@ -650,9 +1093,8 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
         if mouse_pos.0 > 40 {
             let arrow_pos = Point::new(mouse_pos.0 - 2, mouse_pos.1);
             let left_x = mouse_pos.0 - width;
-            let mut y = mouse_pos.1;
 
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
                 ctx.print_color(
                     left_x,
                     y,
@@ -670,7 +1112,6 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                         " ",
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
@@ -682,8 +1123,7 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
         } else {
             let arrow_pos = Point::new(mouse_pos.0 + 1, mouse_pos.1);
             let left_x = mouse_pos.0 + 3;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
                 ctx.print_color(
                     left_x + 1,
                     y,
@@ -701,7 +1141,6 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                         " ",
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
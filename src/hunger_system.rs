@@ -0,0 +1,74 @@
+use super::{
+    initiative_system::TurnClock, DamageType, GameLog, HungerClock, HungerState, RunState,
+    SufferDamage,
+};
+use specs::prelude::*;
+
+/// Turns spent in each hunger state before growing hungrier.
+pub const HUNGER_TURNS: i32 = 300;
+const STARVATION_DAMAGE: i32 = 1;
+
+pub struct HungerSystem {}
+
+impl<'a> System<'a> for HungerSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, TurnClock>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, HungerClock>,
+        WriteStorage<'a, SufferDamage>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, runstate, clock, player_entity, mut log, mut hunger_clocks, mut damage) =
+            data;
+
+        // Hunger grows once per turn, at the end of the monsters' turn
+        if *runstate != RunState::MonsterTurn || !clock.new_turn() {
+            return;
+        }
+
+        for (entity, hunger) in (&entities, &mut hunger_clocks).join() {
+            hunger.duration -= 1;
+            if hunger.duration > 0 {
+                continue;
+            }
+
+            let message = match hunger.state {
+                HungerState::WellFed => {
+                    hunger.state = HungerState::Normal;
+                    hunger.duration = HUNGER_TURNS;
+                    "You are no longer well fed."
+                }
+                HungerState::Normal => {
+                    hunger.state = HungerState::Hungry;
+                    hunger.duration = HUNGER_TURNS;
+                    "You are hungry."
+                }
+                HungerState::Hungry => {
+                    hunger.state = HungerState::Starving;
+                    hunger.duration = HUNGER_TURNS;
+                    "You are starving!"
+                }
+                HungerState::Starving => {
+                    SufferDamage::new_damage(
+                        &mut damage,
+                        entity,
+                        STARVATION_DAMAGE,
+                        DamageType::Starvation,
+                        "starvation",
+                        None,
+                    );
+                    "Your hunger pangs are getting painful!"
+                }
+            };
+
+            if entity == *player_entity {
+                log.entries.push(message.to_string());
+            }
+        }
+    }
+}
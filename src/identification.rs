@@ -0,0 +1,195 @@
+use rltk::{RandomNumberGenerator, RGB};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+const SCROLLS: &[&str] = &[
+    "Magic Missile Scroll",
+    "Fireball Scroll",
+    "Confusion Scroll",
+    "Sleep Scroll",
+    "Teleport Scroll",
+    "Identify Scroll",
+    "Remove Curse Scroll",
+];
+
+const POTIONS: &[&str] = &["Health Potion", "Poison Potion", "Haste Potion"];
+
+const POTION_ADJECTIVES: &[&str] = &[
+    "murky", "bubbling", "smoking", "fizzy", "cloudy", "viscous", "glowing", "swirling",
+];
+
+// Scrolls and potions are drawn in colours shuffled each run, so they can't be told apart by
+// colour either
+const SCROLL_COLOURS: &[(u8, u8, u8)] = &[
+    rltk::CYAN,
+    rltk::ORANGE,
+    rltk::PINK,
+    rltk::MEDIUM_PURPLE,
+    rltk::LIGHT_BLUE,
+    rltk::WHITE,
+    rltk::LIGHT_GREEN,
+    rltk::GOLD,
+    rltk::SALMON,
+];
+
+const POTION_COLOURS: &[(u8, u8, u8)] = &[
+    rltk::MAGENTA,
+    rltk::GREEN,
+    rltk::YELLOW,
+    rltk::RED,
+    rltk::BLUE,
+    rltk::TEAL,
+];
+
+const SYLLABLES: &[&str] = &[
+    "xyz", "zy", "ka", "lo", "rim", "pha", "nor", "ek", "ul", "quo", "ban", "thu",
+];
+
+/// Per-run mapping from the true names of scrolls and potions to the names they are shown under
+/// until identified.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct ItemIdentification {
+    obfuscated: HashMap<String, String>,
+    identified: HashSet<String>,
+    colours: HashMap<String, (u8, u8, u8)>,
+}
+
+impl ItemIdentification {
+    pub fn new(rng: &mut RandomNumberGenerator) -> Self {
+        let mut obfuscated = HashMap::new();
+        let mut colours = HashMap::new();
+
+        for scroll in SCROLLS.iter() {
+            let label = loop {
+                let mut label = String::new();
+                for _ in 0..rng.range(2, 4) {
+                    label.push_str(rng.random_slice_entry(SYLLABLES).unwrap());
+                }
+                let label = format!("scroll labelled {}", label.to_uppercase());
+                if !obfuscated.values().any(|l| *l == label) {
+                    break label;
+                }
+            };
+            obfuscated.insert(scroll.to_string(), label);
+        }
+        shuffle_colours(rng, SCROLLS, SCROLL_COLOURS, &mut colours);
+
+        let mut adjectives = POTION_ADJECTIVES.to_vec();
+        for potion in POTIONS.iter() {
+            let idx = rng.random_slice_index(&adjectives).unwrap();
+            let adjective = adjectives.remove(idx);
+            obfuscated.insert(potion.to_string(), format!("{} potion", adjective));
+        }
+        shuffle_colours(rng, POTIONS, POTION_COLOURS, &mut colours);
+
+        Self {
+            obfuscated,
+            identified: HashSet::new(),
+            colours,
+        }
+    }
+
+    /// The name the player knows an item by.
+    pub fn display_name(&self, name: &str) -> String {
+        match self.obfuscated.get(name) {
+            Some(obfuscated) if !self.identified.contains(name) => obfuscated.clone(),
+            _ => name.to_string(),
+        }
+    }
+
+    /// The colour an item is drawn in this run.
+    pub fn colour(&self, name: &str) -> RGB {
+        self.colours
+            .get(name)
+            .map_or(RGB::named(rltk::WHITE), |c| RGB::named(*c))
+    }
+
+    /// Marks an item type as identified, returning true if it was previously unknown.
+    pub fn identify(&mut self, name: &str) -> bool {
+        self.obfuscated.contains_key(name) && self.identified.insert(name.to_string())
+    }
+}
+
+/// Deals out a colour to each name, only reusing colours once the whole palette has been used.
+fn shuffle_colours(
+    rng: &mut RandomNumberGenerator,
+    names: &[&str],
+    palette: &[(u8, u8, u8)],
+    colours: &mut HashMap<String, (u8, u8, u8)>,
+) {
+    let mut unused = Vec::new();
+    for name in names.iter() {
+        if unused.is_empty() {
+            unused = palette.to_vec();
+        }
+        let idx = rng.random_slice_index(&unused).unwrap();
+        colours.insert(name.to_string(), unused.remove(idx));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unidentified_items_are_obfuscated_until_identified() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let mut identification = ItemIdentification::new(&mut rng);
+
+        let label = identification.display_name("Fireball Scroll");
+        assert!(label.starts_with("scroll labelled "));
+        assert!(identification
+            .display_name("Health Potion")
+            .ends_with(" potion"));
+
+        assert!(identification.identify("Fireball Scroll"));
+        assert!(!identification.identify("Fireball Scroll"));
+        assert_eq!(
+            identification.display_name("Fireball Scroll"),
+            "Fireball Scroll"
+        );
+    }
+
+    #[test]
+    fn ordinary_items_keep_their_names() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let mut identification = ItemIdentification::new(&mut rng);
+
+        assert_eq!(identification.display_name("Longsword"), "Longsword");
+        assert!(!identification.identify("Longsword"));
+    }
+
+    #[test]
+    fn there_are_enough_colours_and_adjectives_to_go_around() {
+        assert!(SCROLL_COLOURS.len() >= SCROLLS.len());
+        assert!(POTION_COLOURS.len() >= POTIONS.len());
+        assert!(POTION_ADJECTIVES.len() >= POTIONS.len());
+    }
+
+    #[test]
+    fn colours_are_reused_once_the_palette_runs_out() {
+        let mut rng = RandomNumberGenerator::seeded(3);
+        let mut colours = HashMap::new();
+        shuffle_colours(&mut rng, SCROLLS, &POTION_COLOURS[..2], &mut colours);
+        assert_eq!(colours.len(), SCROLLS.len());
+        assert!(colours.values().all(|c| POTION_COLOURS[..2].contains(c)));
+    }
+
+    #[test]
+    fn each_item_type_gets_its_own_label_and_colour() {
+        let mut rng = RandomNumberGenerator::seeded(7);
+        let identification = ItemIdentification::new(&mut rng);
+
+        for group in [SCROLLS, POTIONS] {
+            let labels: HashSet<String> = group
+                .iter()
+                .map(|n| identification.display_name(n))
+                .collect();
+            assert_eq!(labels.len(), group.len());
+
+            let colours: HashSet<(u8, u8, u8)> =
+                group.iter().map(|n| identification.colours[*n]).collect();
+            assert_eq!(colours.len(), group.len());
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use super::{Energy, Equipped, MyTurn, RunState, SpeedPenalty, StatusEffect, StatusKind};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+pub const NORMAL_SPEED: i32 = 100;
+/// Four slices make a turn, so a creature can be up to four times as fast as normal and still
+/// get every one of its actions.
+pub const ACTION_COST: i32 = 400;
+const SLICES_PER_TURN: i32 = ACTION_COST / NORMAL_SPEED;
+
+/// Counts the time slices that pass while monsters act. A creature of normal speed acts once per
+/// turn, and things that happen "every turn" such as status effects should only happen then.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct TurnClock {
+    pub slice: i32,
+}
+
+impl TurnClock {
+    pub fn new_turn(&self) -> bool {
+        self.slice % SLICES_PER_TURN == 0
+    }
+
+    pub fn turn(&self) -> i32 {
+        self.slice / SLICES_PER_TURN
+    }
+}
+
+/// Whether the player has built up enough energy to take their next action.
+pub fn player_ready(ecs: &World) -> bool {
+    let energy = ecs.read_storage::<Energy>();
+    energy
+        .get(*ecs.fetch::<Entity>())
+        .is_none_or(|e| e.current >= ACTION_COST)
+}
+
+pub struct InitiativeSystem {}
+
+impl<'a> System<'a> for InitiativeSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, TurnClock>,
+        WriteStorage<'a, Energy>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, SpeedPenalty>,
+        ReadStorage<'a, StatusEffect>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            runstate,
+            player_entity,
+            mut clock,
+            mut energy,
+            mut turns,
+            equipped,
+            penalties,
+            statuses,
+        ) = data;
+
+        turns.clear();
+
+        match *runstate {
+            RunState::PlayerTurn => {
+                if let Some(player_energy) = energy.get_mut(*player_entity) {
+                    player_energy.current -= ACTION_COST;
+                }
+            }
+            RunState::MonsterTurn => {
+                clock.slice += 1;
+
+                for (entity, energy) in (&entities, &mut energy).join() {
+                    let mut speed = energy.speed;
+                    for (equipped_by, penalty) in (&equipped, &penalties).join() {
+                        if equipped_by.owner == entity {
+                            speed -= penalty.penalty;
+                        }
+                    }
+                    for status in statuses.join().filter(|s| s.target == entity) {
+                        match status.kind {
+                            StatusKind::Hasted => speed *= 2,
+                            StatusKind::Slowed => speed /= 2,
+                            _ => {}
+                        }
+                    }
+
+                    // Surplus energy carries over, so partial speed-ups still pay off. Nothing
+                    // acts more than once a slice though, so there's no point banking more than
+                    // a second action.
+                    energy.current = i32::min(2 * ACTION_COST, energy.current + i32::max(1, speed));
+
+                    if entity != *player_entity && energy.current >= ACTION_COST {
+                        energy.current -= ACTION_COST;
+                        turns
+                            .insert(entity, MyTurn {})
+                            .expect("Unable to insert turn");
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use super::{
-    AreaOfEffect, CombatStats, Confusion, Consumable, Equippable, Equipped, GameLog, InBackpack,
-    InflictsDamage, Map, Name, Position, ProvidesHealing, SufferDamage, WantsToDropItem,
-    WantsToPickupItem, WantsToRemoveItem, WantsToUseItem,
+    effects::{EffectQueue, EffectType, Targets},
+    Ammunition, AreaOfEffect, BlocksTile, Cursed, Equipped, GameLog, InBackpack, Item,
+    ItemIdentification, Map, Name, Position, WantsToDropItem, WantsToPickupItem, WantsToRemoveItem,
+    WantsToUseItem,
 };
 use specs::prelude::*;
 
@@ -9,16 +10,39 @@ pub struct ItemRemoveSystem {}
 
 impl<'a> System<'a> for ItemRemoveSystem {
     type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
         Entities<'a>,
         WriteStorage<'a, WantsToRemoveItem>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, Cursed>,
+        ReadStorage<'a, Name>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut wants_remove, mut equipped, mut backpack) = data;
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_remove,
+            mut equipped,
+            mut backpack,
+            cursed,
+            names,
+        ) = data;
 
         for (entity, to_remove) in (&entities, &wants_remove).join() {
+            if cursed.get(to_remove.item).is_some() {
+                if entity == *player_entity {
+                    gamelog.entries.push(format!(
+                        "You cannot remove {}, it is cursed!",
+                        names.get(to_remove.item).unwrap().name
+                    ));
+                }
+                continue;
+            }
+
             equipped.remove(to_remove.item);
             backpack
                 .insert(to_remove.item, InBackpack { owner: entity })
@@ -39,27 +63,56 @@ impl<'a> System<'a> for ItemCollectionSystem {
         WriteStorage<'a, Position>,
         ReadStorage<'a, Name>,
         WriteStorage<'a, InBackpack>,
+        ReadExpect<'a, ItemIdentification>,
+        Entities<'a>,
+        WriteStorage<'a, Ammunition>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) =
-            data;
+        let (
+            player_entity,
+            mut gamelog,
+            mut wants_pickup,
+            mut positions,
+            names,
+            mut backpack,
+            identification,
+            entities,
+            mut ammunition,
+        ) = data;
 
         for pickup in wants_pickup.join() {
             positions.remove(pickup.item);
-            backpack
-                .insert(
-                    pickup.item,
-                    InBackpack {
-                        owner: pickup.collected_by,
-                    },
-                )
-                .expect("Unable to insert backpack entry");
+
+            // Ammunition joins a matching stack if one is already carried
+            let stack = ammunition.get(pickup.item).and_then(|picked| {
+                (&entities, &backpack, &ammunition)
+                    .join()
+                    .find(|(_, pack, carried)| {
+                        pack.owner == pickup.collected_by && carried.ammo_type == picked.ammo_type
+                    })
+                    .map(|(stack, _, carried)| (stack, picked.count + carried.count))
+            });
+            if let Some((stack, count)) = stack {
+                ammunition.get_mut(stack).unwrap().count = count;
+                entities
+                    .delete(pickup.item)
+                    .expect("Unable to delete ammunition");
+            } else {
+                backpack
+                    .insert(
+                        pickup.item,
+                        InBackpack {
+                            owner: pickup.collected_by,
+                        },
+                    )
+                    .expect("Unable to insert backpack entry");
+            }
 
             if pickup.collected_by == *player_entity {
                 gamelog.entries.push(format!(
                     "You pick up the {}.",
-                    names.get(pickup.item).unwrap().name
+                    identification.display_name(&names.get(pickup.item).unwrap().name)
                 ));
             }
         }
@@ -73,194 +126,37 @@ pub struct ItemUseSystem {}
 impl<'a> System<'a> for ItemUseSystem {
     type SystemData = (
         ReadExpect<'a, Map>,
-        ReadExpect<'a, Entity>,
-        WriteExpect<'a, GameLog>,
         Entities<'a>,
         WriteStorage<'a, WantsToUseItem>,
-        ReadStorage<'a, Name>,
-        WriteStorage<'a, Consumable>,
-        ReadStorage<'a, ProvidesHealing>,
-        WriteStorage<'a, CombatStats>,
-        ReadStorage<'a, InflictsDamage>,
-        WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, AreaOfEffect>,
-        WriteStorage<'a, Confusion>,
-        ReadStorage<'a, Equippable>,
-        WriteStorage<'a, Equipped>,
-        WriteStorage<'a, InBackpack>,
+        WriteExpect<'a, EffectQueue>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (
-            map,
-            player_entity,
-            mut gamelog,
-            entities,
-            mut wants_use,
-            names,
-            mut consumables,
-            healing,
-            mut combat_stats,
-            inflict_damage,
-            mut suffer_damage,
-            aoe,
-            mut confused,
-            equippable,
-            mut equipped,
-            mut backpack,
-        ) = data;
+        let (map, entities, mut wants_use, aoe, mut effects) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
-            let mut used_item = true;
-
-            // Targeting
-            let mut targets = Vec::new();
-            match useitem.target {
-                None => {
-                    targets.push(*player_entity);
-                }
-                Some(target) => {
-                    let area_of_effect = aoe.get(useitem.item);
-                    match area_of_effect {
-                        Some(aoe) => {
-                            // AoE
-                            let mut blast_tiles = rltk::field_of_view(target, aoe.radius, &*map);
-                            blast_tiles.retain(|p| {
-                                p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1
-                            });
-
-                            for tile_idx in blast_tiles.iter() {
-                                let idx = map.xy_idx(tile_idx.x, tile_idx.y);
-                                for mob in map.tile_content[idx].iter() {
-                                    targets.push(*mob);
-                                }
-                            }
-                        }
-                        None => {
-                            // Single target in tile
-                            let idx = map.xy_idx(target.x, target.y);
-                            for mob in map.tile_content[idx].iter() {
-                                targets.push(*mob);
-                            }
-                        }
-                    }
-                }
-            }
-
-            // If it is equippable, then we want to equip it - and unequip whatever else was in that slot
-            if let Some(can_equip) = equippable.get(useitem.item) {
-                let target_slot = can_equip.slot;
-                let target = targets[0];
-
-                // remove any item currently equipped in item's slot
-                let mut to_unequip: Vec<Entity> = vec![];
-                for (item_entity, already_equipped, name) in (&entities, &equipped, &names).join() {
-                    if already_equipped.owner == target && already_equipped.slot == target_slot {
-                        to_unequip.push(item_entity);
-                        if target == *player_entity {
-                            gamelog.entries.push(format!("You unequip {}.", name.name));
-                        }
-                    }
-                }
-
-                for item in to_unequip.iter() {
-                    equipped.remove(*item);
-                    backpack
-                        .insert(*item, InBackpack { owner: target })
-                        .expect("Unable to insert backpack entry");
-                }
-
-                // Equip item
-                equipped
-                    .insert(
-                        useitem.item,
-                        Equipped {
-                            owner: target,
-                            slot: target_slot,
-                        },
-                    )
-                    .expect("Unable to insert equipped component");
-                backpack.remove(useitem.item);
-                if target == *player_entity {
-                    gamelog.entries.push(format!(
-                        "You equip {}",
-                        names.get(useitem.item).unwrap().name
-                    ));
-                }
-            }
-
-            // If a healing item, apply the healing.
-            if let Some(healer) = healing.get(useitem.item) {
-                used_item = false;
-
-                for target in targets.iter() {
-                    if let Some(stats) = combat_stats.get_mut(*target) {
-                        stats.hp = i32::min(stats.max_hp, stats.hp + healer.heal_amount);
-                        if entity == *player_entity {
-                            gamelog.entries.push(format!(
-                                "You use the {}, healing {} hp.",
-                                names.get(useitem.item).unwrap().name,
-                                healer.heal_amount
-                            ));
+            // Work out who the item's effects land on
+            let targets = match useitem.target {
+                None => Targets::Single { target: entity },
+                Some(target) => match aoe.get(useitem.item) {
+                    Some(aoe) => {
+                        let mut blast_tiles = rltk::field_of_view(target, aoe.radius, &*map);
+                        blast_tiles.retain(|p| {
+                            p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1
+                        });
+                        Targets::Area {
+                            tiles: blast_tiles.iter().map(|p| map.xy_idx(p.x, p.y)).collect(),
                         }
-                        used_item = true;
-                    }
-                }
-            }
-
-            let mut add_confusion = Vec::new();
-            if let Some(confusion) = confused.get(useitem.item) {
-                used_item = false;
-
-                for mob in targets.iter() {
-                    add_confusion.push((*mob, confusion.turns));
-
-                    if entity == *player_entity {
-                        let mob_name = &names.get(*mob).unwrap().name;
-                        let item_name = &names.get(useitem.item).unwrap().name;
-                        gamelog.entries.push(format!(
-                            "You use {} on {}, confusing them.",
-                            item_name, mob_name,
-                        ));
                     }
-                }
-
-                for (mob, turns) in add_confusion.iter() {
-                    confused
-                        .insert(*mob, Confusion { turns: *turns })
-                        .expect("Unable to insert status");
-
-                    used_item = true;
-                }
-            }
-
-            // If it inflicts damage, apply it to the target cell
-            if let Some(damage) = inflict_damage.get(useitem.item) {
-                used_item = false;
-                for mob in targets.iter() {
-                    SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
-                    if entity == *player_entity {
-                        let mob_name = names.get(*mob).unwrap();
-                        let item_name = names.get(useitem.item).unwrap();
-                        gamelog.entries.push(format!(
-                            "You use {} on {}, inflicting {} hp.",
-                            item_name.name, mob_name.name, damage.damage
-                        ));
-                    }
-
-                    used_item = true;
-                }
-            }
+                    None => Targets::Tile {
+                        tile_idx: map.xy_idx(target.x, target.y),
+                    },
+                },
+            };
 
-            if used_item {
-                if let Some(consumable) = consumables.get_mut(useitem.item) {
-                    if consumable.uses <= 1 {
-                        entities.delete(useitem.item).expect("Delete failed");
-                    } else {
-                        consumable.uses -= 1;
-                    }
-                }
-            }
+            // What the item does is worked out from its components as the effect is applied
+            effects.add_item_effect(entity, useitem.item, EffectType::ItemUse, targets);
         }
 
         wants_use.clear();
@@ -279,6 +175,9 @@ impl<'a> System<'a> for ItemDropSystem {
         ReadStorage<'a, Name>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, InBackpack>,
+        ReadExpect<'a, ItemIdentification>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, BlocksTile>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -291,6 +190,9 @@ impl<'a> System<'a> for ItemDropSystem {
             names,
             mut positions,
             mut backpack,
+            identification,
+            items,
+            blockers,
         ) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
@@ -300,8 +202,13 @@ impl<'a> System<'a> for ItemDropSystem {
             dropper_pos.y = dropped_pos.y;
 
             let idx = map.xy_idx(dropper_pos.x, dropper_pos.y);
-            let item_name = &names.get(to_drop.item).unwrap().name;
-            if map.tile_content[idx].len() > 1 {
+            let item_name = identification.display_name(&names.get(to_drop.item).unwrap().name);
+            // Only other items and solid objects take up floor space, not hidden traps or particles
+            let occupied = map.tile_content[idx]
+                .iter()
+                .filter(|e| **e != entity)
+                .any(|e| items.get(*e).is_some() || blockers.get(*e).is_some());
+            if occupied {
                 if entity == *player_entity {
                     gamelog
                         .entries
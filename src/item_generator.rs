@@ -0,0 +1,287 @@
+use super::{
+    BonusDamage, Cursed, DamageType, DefenseBonus, EquipmentSlot, Equippable, ItemRarity,
+    MagicItem, MeleePowerBonus, Name, RandomTable, RangedWeapon, Renderable, StatusKind,
+    WeaponProc,
+};
+use rltk::{RandomNumberGenerator, RGB};
+use specs::prelude::*;
+
+#[derive(PartialEq, Copy, Clone)]
+enum AffixPosition {
+    Prefix,
+    Suffix,
+}
+
+#[derive(Copy, Clone)]
+enum AffixEffect {
+    Enhancement(i32),
+    Power(i32),
+    Defense(i32),
+    Damage(i32, DamageType),
+    Accuracy(i32),
+    Proc(StatusKind, i32, i32, i32),
+}
+
+struct Affix {
+    name: &'static str,
+    position: AffixPosition,
+    slot: Option<EquipmentSlot>,
+    effect: AffixEffect,
+}
+
+fn affix_table() -> Vec<Affix> {
+    vec![
+        Affix {
+            name: "+1",
+            position: AffixPosition::Prefix,
+            slot: None,
+            effect: AffixEffect::Enhancement(1),
+        },
+        Affix {
+            name: "+2",
+            position: AffixPosition::Prefix,
+            slot: None,
+            effect: AffixEffect::Enhancement(2),
+        },
+        Affix {
+            name: "Flaming",
+            position: AffixPosition::Prefix,
+            slot: Some(EquipmentSlot::Melee),
+            effect: AffixEffect::Damage(2, DamageType::Fire),
+        },
+        Affix {
+            name: "Sturdy",
+            position: AffixPosition::Prefix,
+            slot: Some(EquipmentSlot::Shield),
+            effect: AffixEffect::Defense(1),
+        },
+        Affix {
+            name: "of Warding",
+            position: AffixPosition::Suffix,
+            slot: None,
+            effect: AffixEffect::Defense(1),
+        },
+        Affix {
+            name: "of Might",
+            position: AffixPosition::Suffix,
+            slot: None,
+            effect: AffixEffect::Power(1),
+        },
+        Affix {
+            name: "Venomous",
+            position: AffixPosition::Prefix,
+            slot: Some(EquipmentSlot::Melee),
+            effect: AffixEffect::Proc(StatusKind::Poisoned, 25, 3, 1),
+        },
+        Affix {
+            name: "of Bewilderment",
+            position: AffixPosition::Suffix,
+            slot: Some(EquipmentSlot::Melee),
+            effect: AffixEffect::Proc(StatusKind::Confused, 10, 3, 0),
+        },
+        Affix {
+            name: "of Embers",
+            position: AffixPosition::Suffix,
+            slot: Some(EquipmentSlot::Melee),
+            effect: AffixEffect::Damage(1, DamageType::Fire),
+        },
+    ]
+}
+
+fn rarity_table(map_depth: i32) -> RandomTable {
+    RandomTable::new()
+        .add("Common", 20)
+        .add("Cursed", 2 + map_depth / 2)
+        .add("Magic", 4 + map_depth)
+        .add("Rare", map_depth - 1)
+}
+
+fn rarity_colour(rarity: ItemRarity) -> Option<RGB> {
+    match rarity {
+        ItemRarity::Common => None,
+        ItemRarity::Magic => Some(RGB::named(rltk::DODGER_BLUE)),
+        ItemRarity::Rare => Some(RGB::named(rltk::GOLD)),
+    }
+}
+
+fn pick_affix<'a>(
+    rng: &mut RandomNumberGenerator,
+    affixes: &'a [Affix],
+    position: AffixPosition,
+    slot: EquipmentSlot,
+) -> Option<&'a Affix> {
+    let candidates: Vec<&Affix> = affixes
+        .iter()
+        .filter(|a| a.position == position && a.slot.is_none_or(|s| s == slot))
+        .collect();
+
+    rng.random_slice_entry(&candidates).copied()
+}
+
+/// Cursed items look like their mundane counterparts, but carry a penalty and cannot be removed
+/// once equipped.
+fn curse_item(ecs: &mut World, item: Entity, slot: EquipmentSlot, penalty: i32) {
+    match slot {
+        EquipmentSlot::Melee => {
+            let mut power_bonus = ecs.write_storage::<MeleePowerBonus>();
+            match power_bonus.get_mut(item) {
+                Some(bonus) => bonus.power -= penalty,
+                None => {
+                    power_bonus
+                        .insert(item, MeleePowerBonus { power: -penalty })
+                        .expect("Unable to insert power bonus");
+                }
+            }
+        }
+        EquipmentSlot::Ranged => {
+            if let Some(weapon) = ecs.write_storage::<RangedWeapon>().get_mut(item) {
+                weapon.hit_bonus -= penalty;
+            }
+        }
+        _ => {
+            let mut defense_bonus = ecs.write_storage::<DefenseBonus>();
+            match defense_bonus.get_mut(item) {
+                Some(bonus) => bonus.defense -= penalty,
+                None => {
+                    defense_bonus
+                        .insert(item, DefenseBonus { defense: -penalty })
+                        .expect("Unable to insert defense bonus");
+                }
+            }
+        }
+    }
+
+    ecs.write_storage::<Cursed>()
+        .insert(item, Cursed { revealed: false })
+        .expect("Unable to insert curse");
+}
+
+/// Rolls a rarity for a freshly spawned piece of equipment. Magic items gain a single prefix or
+/// suffix, rare items gain one of each; their bonuses stack onto the base item's and the name and
+/// colour are updated to match.
+pub fn roll_magic_item(ecs: &mut World, item: Entity, map_depth: i32) {
+    let slot = match ecs.read_storage::<Equippable>().get(item) {
+        Some(equippable) => equippable.slot,
+        None => return,
+    };
+
+    let affixes = affix_table();
+    let mut chosen: Vec<&Affix> = Vec::new();
+    let rarity;
+    {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        rarity = match rarity_table(map_depth).roll(&mut rng) {
+            "Cursed" => {
+                let penalty = rng.range(1, 3);
+                std::mem::drop(rng);
+                curse_item(ecs, item, slot, penalty);
+                return;
+            }
+            "Magic" => ItemRarity::Magic,
+            "Rare" => ItemRarity::Rare,
+            _ => ItemRarity::Common,
+        };
+
+        match rarity {
+            ItemRarity::Common => return,
+            ItemRarity::Magic => {
+                let position = if rng.range(0, 2) == 0 {
+                    AffixPosition::Prefix
+                } else {
+                    AffixPosition::Suffix
+                };
+                chosen.extend(pick_affix(&mut rng, &affixes, position, slot));
+            }
+            ItemRarity::Rare => {
+                chosen.extend(pick_affix(&mut rng, &affixes, AffixPosition::Prefix, slot));
+                chosen.extend(pick_affix(&mut rng, &affixes, AffixPosition::Suffix, slot));
+            }
+        }
+    }
+
+    let mut power_bonus = ecs.write_storage::<MeleePowerBonus>();
+    let mut defense_bonus = ecs.write_storage::<DefenseBonus>();
+    let mut bonus_damage = ecs.write_storage::<BonusDamage>();
+    let mut ranged_weapons = ecs.write_storage::<RangedWeapon>();
+    let mut weapon_procs = ecs.write_storage::<WeaponProc>();
+    let mut names = ecs.write_storage::<Name>();
+    let mut renderables = ecs.write_storage::<Renderable>();
+    let mut magic_items = ecs.write_storage::<MagicItem>();
+
+    for affix in chosen.iter() {
+        let effect = match (affix.effect, slot) {
+            (AffixEffect::Enhancement(n), EquipmentSlot::Melee) => AffixEffect::Power(n),
+            (AffixEffect::Enhancement(n), EquipmentSlot::Ranged) => AffixEffect::Accuracy(n),
+            (AffixEffect::Enhancement(n), _) => AffixEffect::Defense(n),
+            (effect, _) => effect,
+        };
+
+        match effect {
+            AffixEffect::Power(n) => match power_bonus.get_mut(item) {
+                Some(bonus) => bonus.power += n,
+                None => {
+                    power_bonus
+                        .insert(item, MeleePowerBonus { power: n })
+                        .expect("Unable to insert power bonus");
+                }
+            },
+            AffixEffect::Defense(n) => match defense_bonus.get_mut(item) {
+                Some(bonus) => bonus.defense += n,
+                None => {
+                    defense_bonus
+                        .insert(item, DefenseBonus { defense: n })
+                        .expect("Unable to insert defense bonus");
+                }
+            },
+            AffixEffect::Damage(n, damage_type) => match bonus_damage.get_mut(item) {
+                Some(bonus) => bonus.damage += n,
+                None => {
+                    bonus_damage
+                        .insert(
+                            item,
+                            BonusDamage {
+                                damage: n,
+                                damage_type,
+                            },
+                        )
+                        .expect("Unable to insert bonus damage");
+                }
+            },
+            AffixEffect::Accuracy(n) => {
+                if let Some(weapon) = ranged_weapons.get_mut(item) {
+                    weapon.hit_bonus += n;
+                }
+            }
+            // A weapon carries a single proc, so the affix's replaces any the base item had
+            AffixEffect::Proc(kind, chance, duration, magnitude) => {
+                weapon_procs
+                    .insert(
+                        item,
+                        WeaponProc {
+                            kind,
+                            chance,
+                            duration,
+                            magnitude,
+                        },
+                    )
+                    .expect("Unable to insert weapon proc");
+            }
+            AffixEffect::Enhancement(_) => unreachable!(),
+        }
+
+        if let Some(name) = names.get_mut(item) {
+            name.name = match affix.position {
+                AffixPosition::Prefix => format!("{} {}", affix.name, name.name),
+                AffixPosition::Suffix => format!("{} {}", name.name, affix.name),
+            };
+        }
+    }
+
+    if let (Some(render), Some(colour)) = (renderables.get_mut(item), rarity_colour(rarity)) {
+        render.fg = colour;
+    }
+
+    magic_items
+        .insert(item, MagicItem { rarity })
+        .expect("Unable to insert magic item");
+}
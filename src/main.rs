@@ -1,4 +1,4 @@
-use rltk::{GameState, Point, Rltk};
+use rltk::{FontCharType, GameState, Point, Rltk, RGB};
 use specs::{
     prelude::*,
     saveload::{SimpleMarker, SimpleMarkerAllocator},
@@ -6,6 +6,9 @@ use specs::{
 
 mod gamelog;
 mod gui;
+mod identification;
+pub use identification::ItemIdentification;
+mod item_generator;
 mod random_table;
 pub use random_table::*;
 pub mod saveload_system;
@@ -17,6 +20,8 @@ mod map;
 pub use map::*;
 mod player;
 use player::*;
+mod raws;
+pub use raws::RawMaster;
 mod rect;
 pub use rect::Rect;
 mod visibility_system;
@@ -27,10 +32,32 @@ mod map_indexing_system;
 use map_indexing_system::MapIndexingSystem;
 mod melee_combat_system;
 use melee_combat_system::MeleeCombatSystem;
+mod ranged_combat_system;
+use ranged_combat_system::RangedCombatSystem;
 mod damage_system;
-use damage_system::DamageSystem;
+use damage_system::{DamageHistory, DamageSystem};
+mod boss_system;
+use boss_system::BossSystem;
 mod inventory_system;
 use inventory_system::*;
+mod status_effect_system;
+use status_effect_system::StatusEffectSystem;
+mod regeneration_system;
+use regeneration_system::RegenerationSystem;
+mod spell_system;
+use spell_system::SpellSystem;
+mod initiative_system;
+use initiative_system::{InitiativeSystem, TurnClock};
+mod durability_system;
+use durability_system::DurabilitySystem;
+mod hunger_system;
+use hunger_system::HungerSystem;
+mod trigger_system;
+use trigger_system::TriggerSystem;
+mod effects;
+use effects::EffectQueue;
+mod particle_system;
+use particle_system::{ParticleBuilder, ParticleSpawnSystem, ProjectileAnimation};
 
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub enum RunState {
@@ -41,6 +68,12 @@ pub enum RunState {
     ShowInventory,
     ShowDropItem,
     ShowRemoveItem,
+    ShowCharacterSheet,
+    ShowEquipment,
+    ShowSpells,
+    ShowFireTargeting {
+        range: i32,
+    },
     ShowTargeting {
         range: i32,
         radius: i32,
@@ -52,6 +85,8 @@ pub enum RunState {
     },
     SaveGame,
     GameOver,
+    Animating,
+    Resting,
 }
 
 pub struct State {
@@ -60,33 +95,116 @@ pub struct State {
 
 impl State {
     fn run_systems(&mut self) {
+        let mut initiative = InitiativeSystem {};
+        initiative.run_now(&self.ecs);
         let mut vis = VisibilitySystem {};
         vis.run_now(&self.ecs);
         let mut mob = MonsterAI {};
         mob.run_now(&self.ecs);
+        let mut bosses = BossSystem {};
+        bosses.run_now(&self.ecs);
         let mut mapindex = MapIndexingSystem {};
         mapindex.run_now(&self.ecs);
+        let mut triggers = TriggerSystem {};
+        triggers.run_now(&self.ecs);
         let mut melee = MeleeCombatSystem {};
         melee.run_now(&self.ecs);
-        let mut damage = DamageSystem {};
-        damage.run_now(&self.ecs);
+        let mut ranged = RangedCombatSystem {};
+        ranged.run_now(&self.ecs);
+        let mut durability = DurabilitySystem {};
+        durability.run_now(&self.ecs);
         let mut pickup = ItemCollectionSystem {};
         pickup.run_now(&self.ecs);
+        let mut spells = SpellSystem {};
+        spells.run_now(&self.ecs);
         let mut use_items = ItemUseSystem {};
         use_items.run_now(&self.ecs);
         let mut drop_items = ItemDropSystem {};
         drop_items.run_now(&self.ecs);
         let mut item_remove = ItemRemoveSystem {};
         item_remove.run_now(&self.ecs);
+        effects::run_effects_queue(&mut self.ecs);
+        let mut status_effects = StatusEffectSystem {};
+        status_effects.run_now(&self.ecs);
+        let mut regeneration = RegenerationSystem {};
+        regeneration.run_now(&self.ecs);
+        let mut hunger = HungerSystem {};
+        hunger.run_now(&self.ecs);
+        let mut damage = DamageSystem {};
+        damage.run_now(&self.ecs);
+        let mut particles = ParticleSpawnSystem {};
+        particles.run_now(&self.ecs);
 
         self.ecs.maintain();
     }
 
+    /// Uses an item or casts a spell, asking for a target first if it needs one.
+    pub fn use_item(&mut self, item: Entity) -> RunState {
+        let player_entity = *self.ecs.fetch::<Entity>();
+
+        // Don't bother aiming a spell that can't be afforded
+        if let Some(spell) = self.ecs.read_storage::<Spell>().get(item) {
+            let mana = self
+                .ecs
+                .read_storage::<CombatStats>()
+                .get(player_entity)
+                .map_or(0, |s| s.mana);
+            if mana < spell.mana_cost {
+                self.ecs
+                    .write_resource::<GameLog>()
+                    .entries
+                    .push("You don't have enough mana.".to_string());
+                return RunState::AwaitingInput;
+            }
+        }
+
+        let is_ranged = self.ecs.read_storage::<Ranged>();
+        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+        let is_aoe = self.ecs.read_storage::<AreaOfEffect>();
+
+        if let Some(ranged_item) = is_ranged.get(item) {
+            let mut radius = 0;
+            if let Some(aoe) = is_aoe.get(item) {
+                radius = aoe.radius;
+            }
+
+            RunState::ShowTargeting {
+                range: ranged_item.range,
+                item,
+                radius,
+            }
+        } else {
+            intent
+                .insert(player_entity, WantsToUseItem { item, target: None })
+                .expect("Unable to insert intent");
+            RunState::PlayerTurn
+        }
+    }
+
+    /// Shows a projectile flying from the player to the target before the turn is resolved.
+    fn launch_projectile(
+        &mut self,
+        target: Option<Point>,
+        glyph: FontCharType,
+        fg: RGB,
+    ) -> RunState {
+        let target = match target {
+            Some(target) => target,
+            None => return RunState::PlayerTurn,
+        };
+        let player_pos = *self.ecs.fetch::<Point>();
+        self.ecs
+            .insert(ProjectileAnimation::new(player_pos, target, glyph, fg));
+        RunState::Animating
+    }
+
     fn entities_to_remove_on_level_change(&mut self) -> Vec<Entity> {
         let entities = self.ecs.entities();
         let player = self.ecs.read_storage::<Player>();
         let backpack = self.ecs.read_storage::<InBackpack>();
         let equipped = self.ecs.read_storage::<Equipped>();
+        let statuses = self.ecs.read_storage::<StatusEffect>();
+        let spellbook = self.ecs.read_storage::<InSpellbook>();
         let player_entity = self.ecs.fetch::<Entity>();
 
         let mut to_delete = vec![];
@@ -107,6 +225,18 @@ impl State {
                 }
             }
 
+            if let Some(s) = statuses.get(entity) {
+                if s.target == *player_entity {
+                    continue;
+                }
+            }
+
+            if let Some(s) = spellbook.get(entity) {
+                if s.owner == *player_entity {
+                    continue;
+                }
+            }
+
             to_delete.push(entity);
         }
 
@@ -136,6 +266,7 @@ impl State {
         for room in worldmap.rooms.iter().skip(1) {
             spawner::spawn_room(&mut self.ecs, room, current_depth + 1);
         }
+        spawner::spawn_boss(&mut self.ecs, &worldmap);
 
         // Place player and update resources
         let (player_x, player_y) = worldmap.rooms[0].center();
@@ -186,10 +317,20 @@ impl State {
             worldmap = worldmap_resource.clone();
         }
 
+        // Forget everything about the previous run
+        *self.ecs.write_resource::<spawner::SpawnedBosses>() = spawner::SpawnedBosses::default();
+        *self.ecs.write_resource::<TurnClock>() = TurnClock::default();
+        *self.ecs.write_resource::<DamageHistory>() = DamageHistory::default();
+        {
+            let mut rng = self.ecs.write_resource::<rltk::RandomNumberGenerator>();
+            *self.ecs.write_resource::<ItemIdentification>() = ItemIdentification::new(&mut rng);
+        }
+
         // Spawn bad guys
         for room in worldmap.rooms.iter().skip(1) {
             spawner::spawn_room(&mut self.ecs, room, 1);
         }
+        spawner::spawn_boss(&mut self.ecs, &worldmap);
 
         // Place the player and update resources
         let (player_x, player_y) = worldmap.rooms[0].center();
@@ -229,19 +370,24 @@ impl GameState for State {
         }
 
         ctx.cls();
+        particle_system::cull_dead_particles(&mut self.ecs, ctx);
 
         match newrunstate {
-            RunState::MainMenu { .. } | RunState::GameOver { .. } => {}
+            RunState::MainMenu { .. } | RunState::GameOver => {}
             _ => {
                 draw_map(&self.ecs, ctx);
 
                 let positions = self.ecs.read_storage::<Position>();
                 let renderables = self.ecs.read_storage::<Renderable>();
+                let hidden = self.ecs.read_storage::<Hidden>();
                 let map = self.ecs.fetch::<Map>();
 
                 // sort renderables by render_order before drawing
-                let mut data = (&positions, &renderables).join().collect::<Vec<_>>();
-                data.sort_by(|a, b| b.1.render_order.cmp(&a.1.render_order));
+                let mut data = (&positions, &renderables, !&hidden)
+                    .join()
+                    .map(|(pos, render, _)| (pos, render))
+                    .collect::<Vec<_>>();
+                data.sort_by_key(|d| std::cmp::Reverse(d.1.render_order));
                 for (pos, render) in data.iter() {
                     let idx = map.xy_idx(pos.x, pos.y);
                     if map.visible_tiles[idx] {
@@ -261,49 +407,39 @@ impl GameState for State {
             RunState::AwaitingInput => {
                 newrunstate = player_input(self, ctx);
             }
+            RunState::Resting => {
+                newrunstate = keep_resting(&mut self.ecs, ctx);
+            }
             RunState::PlayerTurn => {
                 self.run_systems();
                 newrunstate = RunState::MonsterTurn;
             }
             RunState::MonsterTurn => {
+                // Time keeps passing until the player has the energy to act again
                 self.run_systems();
-                newrunstate = RunState::AwaitingInput;
+                if initiative_system::player_ready(&self.ecs) {
+                    let player_entity = *self.ecs.fetch::<Entity>();
+                    newrunstate = if self.ecs.read_storage::<Resting>().contains(player_entity) {
+                        RunState::Resting
+                    } else {
+                        RunState::AwaitingInput
+                    };
+                }
             }
             RunState::ShowInventory => {
                 let (response, item) = gui::show_inventory(self, ctx);
                 match response {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
-                    gui::ItemMenuResult::Selected => {
-                        let item_entity = item.unwrap();
-                        let is_ranged = self.ecs.read_storage::<Ranged>();
-                        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
-                        let is_aoe = self.ecs.read_storage::<AreaOfEffect>();
-
-                        if let Some(ranged_item) = is_ranged.get(item_entity) {
-                            let mut radius = 0;
-                            if let Some(aoe) = is_aoe.get(item_entity) {
-                                radius = aoe.radius;
-                            }
-
-                            newrunstate = RunState::ShowTargeting {
-                                range: ranged_item.range,
-                                item: item_entity,
-                                radius,
-                            };
-                        } else {
-                            intent
-                                .insert(
-                                    *self.ecs.fetch::<Entity>(),
-                                    WantsToUseItem {
-                                        item: item_entity,
-                                        target: None,
-                                    },
-                                )
-                                .expect("Unable to insert intent");
-                            newrunstate = RunState::PlayerTurn;
-                        }
-                    }
+                    gui::ItemMenuResult::Selected => newrunstate = self.use_item(item.unwrap()),
+                }
+            }
+            RunState::ShowSpells => {
+                let (response, spell) = gui::show_spells(self, ctx);
+                match response {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => newrunstate = self.use_item(spell.unwrap()),
                 }
             }
             RunState::ShowDropItem => {
@@ -342,6 +478,16 @@ impl GameState for State {
                     }
                 }
             }
+            RunState::ShowCharacterSheet => {
+                if gui::character_sheet(self, ctx) == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::ShowEquipment => {
+                if gui::equipment_screen(self, ctx) == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
             RunState::ShowTargeting {
                 range,
                 radius,
@@ -352,14 +498,54 @@ impl GameState for State {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
-                        intent
-                            .insert(*self.ecs.fetch::<Entity>(), WantsToUseItem { item, target })
-                            .expect("Unable to insert intent");
-                        newrunstate = RunState::PlayerTurn;
+                        {
+                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToUseItem { item, target },
+                                )
+                                .expect("Unable to insert intent");
+                        }
+                        newrunstate = self.launch_projectile(
+                            target,
+                            rltk::to_cp437('*'),
+                            RGB::named(rltk::ORANGE),
+                        );
+                    }
+                }
+            }
+            RunState::ShowFireTargeting { range } => {
+                let (response, target) = gui::ranged_target(self, ctx, range, 0);
+                match response {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        {
+                            let mut intent = self.ecs.write_storage::<WantsToShoot>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToShoot {
+                                        target: target.unwrap(),
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                        }
+                        newrunstate = self.launch_projectile(
+                            target,
+                            rltk::to_cp437('-'),
+                            RGB::named(rltk::WHITE),
+                        );
                     }
                 }
             }
+            RunState::Animating => {
+                let landed = self.ecs.write_resource::<ProjectileAnimation>().update(ctx);
+                if landed {
+                    newrunstate = RunState::PlayerTurn;
+                }
+            }
             RunState::NextLevel => {
                 self.goto_next_level();
                 newrunstate = RunState::PreRun;
@@ -409,7 +595,7 @@ impl GameState for State {
                 }
             }
             RunState::GameOver => {
-                let result = gui::game_over(ctx);
+                let result = gui::game_over(&self.ecs, ctx);
                 match result {
                     gui::GameOverResult::NoSelection => {}
                     gui::GameOverResult::QuitToMenu => {
@@ -447,6 +633,7 @@ fn main() -> rltk::BError {
     gs.ecs.register::<CombatStats>();
     gs.ecs.register::<WantsToMelee>();
     gs.ecs.register::<SufferDamage>();
+    gs.ecs.register::<SlainBy>();
     gs.ecs.register::<Item>();
     gs.ecs.register::<InBackpack>();
     gs.ecs.register::<WantsToPickupItem>();
@@ -457,14 +644,58 @@ fn main() -> rltk::BError {
     gs.ecs.register::<Ranged>();
     gs.ecs.register::<InflictsDamage>();
     gs.ecs.register::<AreaOfEffect>();
-    gs.ecs.register::<Confusion>();
+    gs.ecs.register::<InflictsStatus>();
+    gs.ecs.register::<StatusEffect>();
+    gs.ecs.register::<ApplyStatus>();
+    gs.ecs.register::<Energy>();
+    gs.ecs.register::<MyTurn>();
+    gs.ecs.register::<SpeedPenalty>();
+    gs.ecs.register::<RangedWeapon>();
+    gs.ecs.register::<Ammunition>();
+    gs.ecs.register::<WantsToShoot>();
+    gs.ecs.register::<Spell>();
+    gs.ecs.register::<InSpellbook>();
+    gs.ecs.register::<TeachesSpell>();
     gs.ecs.register::<SimpleMarker<SerializeMe>>();
     gs.ecs.register::<SerializationHelper>();
     gs.ecs.register::<Equippable>();
     gs.ecs.register::<Equipped>();
     gs.ecs.register::<DefenseBonus>();
     gs.ecs.register::<MeleePowerBonus>();
+    gs.ecs.register::<HealthRegenBonus>();
+    gs.ecs.register::<Resting>();
     gs.ecs.register::<WantsToRemoveItem>();
+    gs.ecs.register::<Boss>();
+    gs.ecs.register::<BonusDamage>();
+    gs.ecs.register::<CriticalHit>();
+    gs.ecs.register::<WeaponProc>();
+    gs.ecs.register::<TwoHanded>();
+    gs.ecs.register::<OffHand>();
+    gs.ecs.register::<Reach>();
+    gs.ecs.register::<Durability>();
+    gs.ecs.register::<SufferWear>();
+    gs.ecs.register::<RepairsItems>();
+    gs.ecs.register::<HungerClock>();
+    gs.ecs.register::<ProvidesFood>();
+    gs.ecs.register::<Hidden>();
+    gs.ecs.register::<EntryTrigger>();
+    gs.ecs.register::<EntityMoved>();
+    gs.ecs.register::<SingleActivation>();
+    gs.ecs.register::<TeleportsTarget>();
+    gs.ecs.register::<Knockback>();
+    gs.ecs.register::<PullsTarget>();
+    gs.ecs.register::<MagicItem>();
+    gs.ecs.register::<ProvidesIdentification>();
+    gs.ecs.register::<Cursed>();
+    gs.ecs.register::<RemovesCurse>();
+    gs.ecs.register::<Attributes>();
+    gs.ecs.register::<MeleeWeapon>();
+    gs.ecs.register::<Experience>();
+    gs.ecs.register::<LevelUpFlash>();
+    gs.ecs.register::<ParticleLifetime>();
+    gs.ecs.register::<Skills>();
+    gs.ecs.register::<NaturalAttackDefense>();
+    gs.ecs.register::<Resistances>();
 
     gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
 
@@ -473,10 +704,19 @@ fn main() -> rltk::BError {
 
     let player_entity = spawner::player(&mut gs.ecs, player_x, player_y);
 
-    gs.ecs.insert(rltk::RandomNumberGenerator::new());
+    gs.ecs.insert(RawMaster::embedded());
+    let mut rng = rltk::RandomNumberGenerator::new();
+    gs.ecs.insert(ItemIdentification::new(&mut rng));
+    gs.ecs.insert(rng);
+    gs.ecs.insert(spawner::SpawnedBosses::default());
+    gs.ecs.insert(TurnClock::default());
+    gs.ecs.insert(DamageHistory::default());
+    gs.ecs.insert(EffectQueue::default());
+    gs.ecs.insert(ParticleBuilder::default());
     for room in map.rooms.iter().skip(1) {
         spawner::spawn_room(&mut gs.ecs, room, 1);
     }
+    spawner::spawn_boss(&mut gs.ecs, &map);
 
     gs.ecs.insert(map);
     gs.ecs.insert(Point::new(player_x, player_y));
@@ -24,6 +24,7 @@ pub struct Map {
     pub revealed_tiles: Vec<bool>,
     pub visible_tiles: Vec<bool>,
     pub blocked: Vec<bool>,
+    pub known_traps: Vec<bool>,
     pub depth: i32,
 
     #[serde(skip_serializing)]
@@ -68,8 +69,9 @@ impl Map {
             return false;
         }
 
+        // Monsters steer around the traps they know about
         let idx = self.xy_idx(x, y);
-        !self.blocked[idx]
+        !self.blocked[idx] && !self.known_traps[idx]
     }
 
     pub fn populate_blocked(&mut self) {
@@ -93,7 +95,7 @@ impl Algorithm2D for Map {
 
 impl BaseMap for Map {
     fn is_opaque(&self, idx: usize) -> bool {
-        self.tiles[idx as usize] == TileType::Wall
+        self.tiles[idx] == TileType::Wall
     }
 
     fn get_available_exits(&self, idx: usize) -> rltk::SmallVec<[(usize, f32); 10]> {
@@ -151,6 +153,7 @@ pub fn new_map_rooms_and_corridors(depth: i32) -> Map {
         revealed_tiles: vec![false; MAPCOUNT],
         visible_tiles: vec![false; MAPCOUNT],
         blocked: vec![false; MAPCOUNT],
+        known_traps: vec![false; MAPCOUNT],
         tile_content: vec![Vec::new(); MAPCOUNT],
         depth,
     };
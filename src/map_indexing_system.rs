@@ -1,4 +1,4 @@
-use super::{BlocksTile, Map, Position};
+use super::{BlocksTile, EntryTrigger, Hidden, Map, Position};
 use specs::prelude::*;
 
 pub struct MapIndexingSystem {}
@@ -8,21 +8,29 @@ impl<'a> System<'a> for MapIndexingSystem {
         WriteExpect<'a, Map>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, BlocksTile>,
+        ReadStorage<'a, EntryTrigger>,
+        ReadStorage<'a, Hidden>,
         Entities<'a>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, position, blockers, entities) = data;
+        let (mut map, position, blockers, triggers, hidden, entities) = data;
 
         map.populate_blocked();
         map.clear_content_index();
+        for known in map.known_traps.iter_mut() {
+            *known = false;
+        }
 
         for (entity, position) in (&entities, &position).join() {
             let idx = map.xy_idx(position.x, position.y);
 
-            if let Some(_) = blockers.get(entity) {
+            if blockers.get(entity).is_some() {
                 map.blocked[idx] = true;
             }
+            if triggers.get(entity).is_some() && hidden.get(entity).is_none() {
+                map.known_traps[idx] = true;
+            }
 
             map.tile_content[idx].push(entity);
         }
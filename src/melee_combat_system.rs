@@ -1,76 +1,422 @@
 use super::{
-    CombatStats, DefenseBonus, Equipped, GameLog, MeleePowerBonus, Name, SufferDamage, WantsToMelee,
+    damage_system::total_damage_taken,
+    durability_system::{armor_worn_by, blunted, wear_from_hit},
+    effects::{EffectQueue, EffectType, Targets},
+    particle_system::PARTICLE_LIFETIME_MS,
+    Attributes, BonusDamage, CombatStats, CriticalHit, DamageType, DefenseBonus, Durability,
+    EquipmentSlot, Equipped, GameLog, Knockback, MeleePowerBonus, MeleeWeapon, Name,
+    NaturalAttackDefense, Position, Reach, Resistances, Skill, Skills, StatusKind, SufferDamage,
+    SufferWear, WantsToMelee, WeaponProc,
 };
+use rltk::{RandomNumberGenerator, RGB};
 use specs::prelude::*;
 
+pub const BASE_ARMOR_CLASS: i32 = 10;
+
+/// To-hit penalty for the extra attack made with a weapon in the off hand.
+const OFF_HAND_PENALTY: i32 = 4;
+
+/// The defense an owner gets from their equipment, less whatever wear has taken off it.
+pub fn equipment_defense(
+    owner: Entity,
+    entities: &Entities,
+    equipped: &ReadStorage<Equipped>,
+    defense_bonus: &ReadStorage<DefenseBonus>,
+    durability: &ReadStorage<Durability>,
+) -> i32 {
+    (entities, equipped, defense_bonus)
+        .join()
+        .filter(|(_, equipped_by, _)| equipped_by.owner == owner)
+        .map(|(item, _, bonus)| {
+            durability
+                .get(item)
+                .map_or(bonus.defense, |d| d.worn(bonus.defense))
+        })
+        .sum()
+}
+
+/// What an attack roll has to beat to hit the target.
+pub fn armor_class(
+    target: Entity,
+    stats: &CombatStats,
+    equipment_defense: i32,
+    natural_attacks: &ReadStorage<NaturalAttackDefense>,
+    attributes: &ReadStorage<Attributes>,
+    skills: &WriteStorage<Skills>,
+) -> i32 {
+    let natural_armor = natural_attacks.get(target).map_or(0, |n| n.armor);
+    let quickness = attributes.get(target).map_or(0, |a| a.quickness.bonus());
+    let defense_skill = skills.get(target).map_or(0, |s| s.rank(Skill::Defense));
+    BASE_ARMOR_CLASS + stats.defense + equipment_defense + natural_armor + quickness + defense_skill
+}
+
+/// One attack made as part of a melee action, with a weapon, the off hand or a natural weapon.
+struct Swing {
+    weapon: MeleeWeapon,
+    item: Option<Entity>,
+    critical: CriticalHit,
+    procs: Vec<WeaponProc>,
+    strikes: i32,
+    damage_bonus: i32,
+    hit_penalty: i32,
+    with_attack: String,
+    /// Enchantments on the weapon itself, which only count when striking with it.
+    power_bonus: i32,
+    extra_damage: Vec<(i32, DamageType)>,
+}
+
+impl Swing {
+    /// Fighting unarmed is a 1d4 punch.
+    fn unarmed() -> Swing {
+        Swing {
+            weapon: MeleeWeapon {
+                damage_n_dice: 1,
+                damage_die_type: 4,
+                damage_type: DamageType::Bludgeoning,
+                hit_bonus: 0,
+            },
+            item: None,
+            critical: CriticalHit {
+                threat: 20,
+                multiplier: 2,
+            },
+            procs: Vec::new(),
+            strikes: 1,
+            damage_bonus: 0,
+            hit_penalty: 0,
+            with_attack: String::new(),
+            power_bonus: 0,
+            extra_damage: Vec::new(),
+        }
+    }
+
+    /// The weapon's own damage from a hit, before the target's resistances.
+    fn roll_damage(
+        &self,
+        rng: &mut RandomNumberGenerator,
+        bonus: i32,
+        is_critical: bool,
+        durability: &ReadStorage<Durability>,
+    ) -> i32 {
+        let mut damage = i32::max(
+            1,
+            rng.roll_dice(self.weapon.damage_n_dice, self.weapon.damage_die_type)
+                + self.damage_bonus
+                + self.power_bonus
+                + bonus,
+        );
+        if is_critical {
+            damage *= self.critical.multiplier;
+        }
+        match self.item {
+            Some(item) => blunted(damage, item, durability),
+            None => damage,
+        }
+    }
+}
+
+/// What the log says when a weapon's special effect takes hold of its target.
+fn proc_message(kind: StatusKind, target_name: &str) -> String {
+    match kind {
+        StatusKind::Poisoned => format!("Venom seeps into {}'s wound!", target_name),
+        StatusKind::Confused => format!("{} reels, dazed by the blow!", target_name),
+        StatusKind::Stunned => format!("{} is knocked senseless!", target_name),
+        kind => format!("{} is {}!", target_name, kind.name()),
+    }
+}
+
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         Entities<'a>,
         WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
         WriteStorage<'a, WantsToMelee>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Attributes>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, MeleePowerBonus>,
         ReadStorage<'a, DefenseBonus>,
         ReadStorage<'a, Equipped>,
+        ReadStorage<'a, BonusDamage>,
+        ReadStorage<'a, MeleeWeapon>,
+        ReadStorage<'a, NaturalAttackDefense>,
+        WriteStorage<'a, Skills>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, CriticalHit>,
+        ReadStorage<'a, WeaponProc>,
+        WriteExpect<'a, EffectQueue>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Reach>,
+        ReadStorage<'a, Durability>,
+        ReadStorage<'a, Resistances>,
+        WriteStorage<'a, SufferWear>,
+        ReadStorage<'a, Knockback>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             entities,
             mut log,
+            mut rng,
             mut wants_melee,
             names,
             combat_stats,
+            attributes,
             mut inflict_damage,
             melee_power_bonus,
             defense_bonus,
             equipped,
+            bonus_damage,
+            melee_weapons,
+            natural_attacks,
+            mut skills,
+            player_entity,
+            critical_hits,
+            weapon_procs,
+            mut effects,
+            positions,
+            reaches,
+            durability,
+            resistances,
+            mut wear,
+            knockback,
         ) = data;
 
         for (entity, wants_melee, name, stats) in
             (&entities, &wants_melee, &names, &combat_stats).join()
         {
-            if stats.hp > 0 {
-                let target_stats = combat_stats.get(wants_melee.target).unwrap();
-                if target_stats.hp > 0 {
-                    let mut offensive_bonus = 0;
-                    for (_item_entity, melee_bonus, equipped_by) in
-                        (&entities, &melee_power_bonus, &equipped).join()
-                    {
-                        if equipped_by.owner == entity {
-                            offensive_bonus += melee_bonus.power;
-                        }
+            let target = wants_melee.target;
+            let target_stats = combat_stats.get(target).unwrap();
+            if stats.hp <= 0 || target_stats.hp <= 0 {
+                continue;
+            }
+            let target_name = names.get(target).unwrap();
+
+            let mut main_hand = Swing::unarmed();
+            let mut off_hand = None;
+            let mut offensive_bonus = 0;
+            let mut extra_damage = Vec::new();
+            for (item_entity, equipped_by) in (&entities, &equipped).join() {
+                if equipped_by.owner != entity {
+                    continue;
+                }
+                let power = melee_power_bonus.get(item_entity).map_or(0, |b| {
+                    durability
+                        .get(item_entity)
+                        .map_or(b.power, |d| d.worn(b.power))
+                });
+                // Enchanted weapons deal extra damage that ignores defense
+                let bonus = bonus_damage
+                    .get(item_entity)
+                    .map(|b| (b.damage, b.damage_type));
+
+                let weapon = match melee_weapons.get(item_entity) {
+                    Some(weapon) => weapon,
+                    // Rings, amulets and the like empower every swing
+                    None => {
+                        offensive_bonus += power;
+                        extra_damage.extend(bonus);
+                        continue;
                     }
+                };
+                let swing = Swing {
+                    weapon: weapon.clone(),
+                    item: Some(item_entity),
+                    critical: critical_hits
+                        .get(item_entity)
+                        .cloned()
+                        .unwrap_or(main_hand.critical.clone()),
+                    procs: weapon_procs.get(item_entity).cloned().into_iter().collect(),
+                    power_bonus: power,
+                    extra_damage: bonus.into_iter().collect(),
+                    ..Swing::unarmed()
+                };
+                if equipped_by.slot == EquipmentSlot::Shield {
+                    let weapon_name = names
+                        .get(item_entity)
+                        .map_or("weapon".to_string(), |n| n.name.clone());
+                    off_hand = Some(Swing {
+                        hit_penalty: OFF_HAND_PENALTY,
+                        with_attack: format!(" with the off-hand {}", weapon_name),
+                        ..swing
+                    });
+                } else {
+                    main_hand = swing;
+                }
+            }
 
-                    let target_name = names.get(wants_melee.target).unwrap();
-                    let mut defensive_bonus = 0;
-                    for (_item_entity, defense_bonus, equipped_by) in
-                        (&entities, &defense_bonus, &equipped).join()
-                    {
-                        if equipped_by.owner == wants_melee.target {
-                            defensive_bonus += defense_bonus.defense;
+            // Weapons without reach can only strike adjacent foes
+            let reach = main_hand
+                .item
+                .and_then(|item| reaches.get(item))
+                .map_or(1, |r| r.range);
+            if let (Some(pos), Some(target_pos)) = (positions.get(entity), positions.get(target)) {
+                let distance = i32::max((pos.x - target_pos.x).abs(), (pos.y - target_pos.y).abs());
+                if distance > reach {
+                    continue;
+                }
+            }
+
+            // Creatures that aren't wielding a weapon pick one of their natural attacks
+            if let Some(natural) = natural_attacks.get(entity) {
+                if main_hand.item.is_none() && !natural.attacks.is_empty() {
+                    let attack = rng.random_slice_entry(&natural.attacks).unwrap();
+                    main_hand.weapon = MeleeWeapon {
+                        damage_n_dice: attack.damage_n_dice,
+                        damage_die_type: attack.damage_die_type,
+                        damage_type: attack.damage_type,
+                        hit_bonus: attack.hit_bonus,
+                    };
+                    main_hand.with_attack = format!(" with its {}", attack.name);
+                    main_hand.strikes = attack.strikes;
+                    main_hand.damage_bonus = attack.damage_bonus;
+                }
+            }
+
+            let defense =
+                equipment_defense(target, &entities, &equipped, &defense_bonus, &durability);
+            let armor_class = armor_class(
+                target,
+                target_stats,
+                defense,
+                &natural_attacks,
+                &attributes,
+                &skills,
+            );
+            let armor = armor_worn_by(target, &entities, &equipped, &durability, &melee_weapons);
+            let might = attributes.get(entity).map_or(0, |a| a.might.bonus());
+
+            'swings: for swing in std::iter::once(main_hand).chain(off_hand) {
+                let with_attack = &swing.with_attack;
+                for _ in 0..swing.strikes {
+                    let melee_skill = skills.get(entity).map_or(0, |s| s.rank(Skill::Melee));
+                    let natural_roll = rng.roll_dice(1, 20);
+                    let attack_roll = natural_roll + might + swing.weapon.hit_bonus + melee_skill
+                        - swing.hit_penalty;
+
+                    if natural_roll == 1 {
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Fumble {
+                                weapon: swing.item,
+                                with_attack: with_attack.clone(),
+                            },
+                            Targets::Single { target },
+                        );
+                        break 'swings;
+                    }
+
+                    // Rolls within the weapon's threat range crit if they would hit anyway, a
+                    // natural 20 always does
+                    let is_critical = natural_roll == 20
+                        || (natural_roll >= swing.critical.threat && attack_roll >= armor_class);
+                    if !is_critical && attack_roll < armor_class {
+                        log.entries.push(format!(
+                            "{} attacks {}{}, but misses.",
+                            &name.name, &target_name.name, with_attack
+                        ));
+
+                        if let Some(rank) =
+                            Skills::practice_skill(&mut skills, target, Skill::Defense)
+                        {
+                            if target == *player_entity {
+                                log.entries
+                                    .push(format!("Your defense skill improves to {}!", rank));
+                            }
                         }
+                        continue;
                     }
-                    let damage = i32::max(
-                        0,
-                        (stats.power + offensive_bonus) - (target_stats.defense + defensive_bonus),
-                    );
 
-                    if damage == 0 {
+                    let weapon_damage = swing.roll_damage(
+                        &mut rng,
+                        might + stats.power + offensive_bonus,
+                        is_critical,
+                        &durability,
+                    );
+                    let damage: Vec<(i32, DamageType)> =
+                        std::iter::once((weapon_damage, swing.weapon.damage_type))
+                            .chain(extra_damage.iter().copied())
+                            .chain(swing.extra_damage.iter().copied())
+                            .collect();
+                    // Report what actually gets through the target's resistances
+                    let taken =
+                        total_damage_taken(target, &damage, &entities, &resistances, &equipped);
+                    if is_critical {
                         log.entries.push(format!(
-                            "{} is unable to hurt {}",
-                            &name.name, &target_name.name
+                            "{} lands a critical hit on {}{}, for {} hp!",
+                            &name.name, &target_name.name, with_attack, taken
                         ));
                     } else {
                         log.entries.push(format!(
-                            "{} hits {}, for {} hp.",
-                            &name.name, &target_name.name, damage
+                            "{} hits {}{}, for {} hp.",
+                            &name.name, &target_name.name, with_attack, taken
                         ));
-                        SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
+                    }
+                    let source = match swing.item.and_then(|i| names.get(i)) {
+                        Some(item) => format!("{}'s {}", &name.name, item.name),
+                        None => format!("{}{}", &name.name, with_attack),
+                    };
+                    for (amount, damage_type) in damage {
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            target,
+                            amount,
+                            damage_type,
+                            &source,
+                            Some(entity),
+                        );
+                    }
+
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Particle {
+                            glyph: rltk::to_cp437('‼'),
+                            fg: RGB::named(rltk::RED),
+                            bg: RGB::named(rltk::BLACK),
+                            lifetime: PARTICLE_LIFETIME_MS,
+                        },
+                        Targets::Single { target },
+                    );
+
+                    // Heavy weapons send their victims flying
+                    if let Some(knockback) = swing.item.and_then(|i| knockback.get(i)) {
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Push {
+                                distance: knockback.distance,
+                            },
+                            Targets::Single { target },
+                        );
+                    }
+
+                    wear_from_hit(&mut wear, &mut rng, swing.item, &armor);
+
+                    for weapon_proc in swing.procs.iter() {
+                        if rng.roll_dice(1, 100) > weapon_proc.chance {
+                            continue;
+                        }
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Status {
+                                kind: weapon_proc.kind,
+                                duration: weapon_proc.duration,
+                                magnitude: weapon_proc.magnitude,
+                            },
+                            Targets::Single { target },
+                        );
+                        log.entries
+                            .push(proc_message(weapon_proc.kind, &target_name.name));
+                    }
+
+                    if let Some(rank) = Skills::practice_skill(&mut skills, entity, Skill::Melee) {
+                        if entity == *player_entity {
+                            log.entries
+                                .push(format!("Your melee skill improves to {}!", rank));
+                        }
                     }
                 }
             }
@@ -1,4 +1,7 @@
-use super::{Confusion, Map, Monster, Position, RunState, Viewshed, WantsToMelee};
+use super::{
+    status_effect_system::active_statuses, Boss, EntityMoved, Map, Monster, MyTurn, Position,
+    RunState, StatusEffect, StatusKind, Viewshed, WantsToMelee,
+};
 use rltk::Point;
 use specs::prelude::*;
 
@@ -16,7 +19,10 @@ impl<'a> System<'a> for MonsterAI {
         ReadStorage<'a, Monster>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, StatusEffect>,
+        ReadStorage<'a, Boss>,
+        ReadStorage<'a, MyTurn>,
+        WriteStorage<'a, EntityMoved>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -30,27 +36,24 @@ impl<'a> System<'a> for MonsterAI {
             monster,
             mut position,
             mut wants_to_melee,
-            mut confused,
+            statuses,
+            bosses,
+            turns,
+            mut moved,
         ) = data;
 
         if *runstate != RunState::MonsterTurn {
             return;
         }
 
-        for (entity, mut viewshed, _monster, mut pos) in
-            (&entities, &mut viewshed, &monster, &mut position).join()
-        {
-            let mut can_act = true;
-
-            if let Some(i_am_confused) = confused.get_mut(entity) {
-                i_am_confused.turns -= 1;
-
-                if i_am_confused.turns < 1 {
-                    confused.remove(entity);
-                }
+        let afflicted = active_statuses(&statuses);
 
-                can_act = false;
-            }
+        for (entity, viewshed, _monster, pos, _turn) in
+            (&entities, &mut viewshed, &monster, &mut position, &turns).join()
+        {
+            let can_act = !afflicted.contains(&(entity, StatusKind::Confused))
+                && !afflicted.contains(&(entity, StatusKind::Asleep))
+                && !afflicted.contains(&(entity, StatusKind::Stunned));
 
             if can_act {
                 let distance =
@@ -64,12 +67,14 @@ impl<'a> System<'a> for MonsterAI {
                             },
                         )
                         .expect("Unable to insert attack");
-                } else if viewshed.visible_tiles.contains(&*player_pos) {
-                    // Path to the player
+                } else if bosses.get(entity).is_none()
+                    && viewshed.visible_tiles.contains(&*player_pos)
+                {
+                    // Path to the player (bosses hold their ground on the downstairs)
                     let path = rltk::a_star_search(
                         map.xy_idx(pos.x, pos.y),
                         map.xy_idx(player_pos.x, player_pos.y),
-                        &mut *map,
+                        &*map,
                     );
                     if path.success && path.steps.len() > 1 {
                         let mut idx = map.xy_idx(pos.x, pos.y);
@@ -79,6 +84,9 @@ impl<'a> System<'a> for MonsterAI {
                         idx = map.xy_idx(pos.x, pos.y);
                         map.blocked[idx] = true;
                         viewshed.dirty = true;
+                        moved
+                            .insert(entity, EntityMoved {})
+                            .expect("Unable to insert marker");
                     }
                 }
             }
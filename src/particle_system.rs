@@ -0,0 +1,155 @@
+use super::{ParticleLifetime, Position, Renderable};
+use rltk::{FontCharType, Point, Rltk, RGB};
+use specs::prelude::*;
+
+/// How long combat and spell particles stay on screen.
+pub const PARTICLE_LIFETIME_MS: f32 = 200.0;
+
+/// How long a projectile takes to cross each tile of its flight.
+const PROJECTILE_STEP_MS: f32 = 30.0;
+
+struct ParticleRequest {
+    x: i32,
+    y: i32,
+    fg: RGB,
+    bg: RGB,
+    glyph: FontCharType,
+    lifetime: f32,
+}
+
+/// Particles waiting to be spawned by the `ParticleSpawnSystem`.
+#[derive(Default)]
+pub struct ParticleBuilder {
+    requests: Vec<ParticleRequest>,
+}
+
+impl ParticleBuilder {
+    pub fn request(
+        &mut self,
+        x: i32,
+        y: i32,
+        fg: RGB,
+        bg: RGB,
+        glyph: FontCharType,
+        lifetime: f32,
+    ) {
+        self.requests.push(ParticleRequest {
+            x,
+            y,
+            fg,
+            bg,
+            glyph,
+            lifetime,
+        });
+    }
+}
+
+pub struct ParticleSpawnSystem {}
+
+impl<'a> System<'a> for ParticleSpawnSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Renderable>,
+        WriteStorage<'a, ParticleLifetime>,
+        WriteExpect<'a, ParticleBuilder>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut positions, mut renderables, mut particles, mut particle_builder) = data;
+
+        for new_particle in particle_builder.requests.drain(..) {
+            let p = entities.create();
+            positions
+                .insert(
+                    p,
+                    Position {
+                        x: new_particle.x,
+                        y: new_particle.y,
+                    },
+                )
+                .expect("Unable to insert position");
+            renderables
+                .insert(
+                    p,
+                    Renderable {
+                        fg: new_particle.fg,
+                        bg: new_particle.bg,
+                        glyph: new_particle.glyph,
+                        render_order: 0,
+                    },
+                )
+                .expect("Unable to insert renderable");
+            particles
+                .insert(
+                    p,
+                    ParticleLifetime {
+                        lifetime_ms: new_particle.lifetime,
+                    },
+                )
+                .expect("Unable to insert lifetime");
+        }
+    }
+}
+
+/// Ages every particle by the time the last frame took, removing those that have expired.
+pub fn cull_dead_particles(ecs: &mut World, ctx: &Rltk) {
+    let mut dead_particles: Vec<Entity> = Vec::new();
+    {
+        let mut particles = ecs.write_storage::<ParticleLifetime>();
+        let entities = ecs.entities();
+        for (entity, particle) in (&entities, &mut particles).join() {
+            particle.lifetime_ms -= ctx.frame_time_ms;
+            if particle.lifetime_ms < 0.0 {
+                dead_particles.push(entity);
+            }
+        }
+    }
+    for dead in dead_particles.iter() {
+        ecs.delete_entity(*dead).expect("Particle will not die");
+    }
+}
+
+/// A projectile in flight. Play stops while it travels, and resumes once it lands.
+pub struct ProjectileAnimation {
+    path: Vec<Point>,
+    glyph: FontCharType,
+    fg: RGB,
+    elapsed_ms: f32,
+}
+
+impl ProjectileAnimation {
+    pub fn new(from: Point, to: Point, glyph: FontCharType, fg: RGB) -> Self {
+        // The first point is the shooter's own tile
+        let path = rltk::line2d(rltk::LineAlg::Bresenham, from, to)
+            .into_iter()
+            .skip(1)
+            .collect();
+        ProjectileAnimation {
+            path,
+            glyph,
+            fg,
+            elapsed_ms: 0.0,
+        }
+    }
+
+    /// Advances and draws the projectile, returning true once it has reached its target.
+    pub fn update(&mut self, ctx: &mut Rltk) -> bool {
+        self.elapsed_ms += ctx.frame_time_ms;
+        let step = (self.elapsed_ms / PROJECTILE_STEP_MS) as usize;
+        match self.path.get(step) {
+            Some(point) => {
+                ctx.set(
+                    point.x,
+                    point.y,
+                    self.fg,
+                    RGB::named(rltk::BLACK),
+                    self.glyph,
+                );
+                false
+            }
+            None => true,
+        }
+    }
+}
@@ -1,12 +1,36 @@
 use super::{
-    CombatStats, GameLog, Item, Map, Monster, Player, Position, RunState, State, TileType,
-    Viewshed, WantsToMelee, WantsToPickupItem,
+    spell_system::known_spells, CombatStats, EntityMoved, EquipmentSlot, Equipped, GameLog,
+    HungerClock, HungerState, Item, Map, Monster, Name, Player, Position, RangedWeapon, Reach,
+    Resting, RunState, Skill, Skills, State, StatusEffect, StatusKind, TileType, Viewshed,
+    WantsToMelee, WantsToPickupItem,
 };
-use rltk::{Point, Rltk, VirtualKeyCode};
+use rltk::{Point, RandomNumberGenerator, Rltk, VirtualKeyCode};
 use specs::prelude::*;
-use std::cmp::{max, min};
 
-pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+fn player_has_status(ecs: &World, kind: StatusKind) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let statuses = ecs.read_storage::<StatusEffect>();
+    statuses
+        .join()
+        .any(|s| s.target == *player_entity && s.kind == kind)
+}
+
+pub fn try_move_player(mut delta_x: i32, mut delta_y: i32, ecs: &mut World) {
+    // The confused stagger in a random direction
+    if player_has_status(ecs, StatusKind::Confused) {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        loop {
+            delta_x = rng.range(-1, 2);
+            delta_y = rng.range(-1, 2);
+            if (delta_x, delta_y) != (0, 0) {
+                break;
+            }
+        }
+        ecs.write_resource::<GameLog>()
+            .entries
+            .push("You stumble about in confusion.".to_string());
+    }
+
     let mut positions = ecs.write_storage::<Position>();
     let players = ecs.read_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
@@ -14,6 +38,9 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let combat_stats = ecs.read_storage::<CombatStats>();
     let map = ecs.fetch::<Map>();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let reaches = ecs.read_storage::<Reach>();
+    let mut moved = ecs.write_storage::<EntityMoved>();
 
     for (entity, _player, pos, viewshed) in
         (&entities, &players, &mut positions, &mut viewsheds).join()
@@ -27,26 +54,51 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
         }
         let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
-        for potential_target in map.tile_content[destination_idx].iter() {
-            let target = combat_stats.get(*potential_target);
-            if let Some(_target) = target {
-                wants_to_melee
-                    .insert(
-                        entity,
-                        WantsToMelee {
-                            target: *potential_target,
-                        },
-                    )
-                    .expect("Add target failed");
-                return;
+        // A reach weapon in the main hand strikes the first foe in line, as long as no wall is in
+        // the way
+        let reach = (&equipped, &reaches)
+            .join()
+            .find(|(equipped_by, _)| {
+                equipped_by.owner == entity && equipped_by.slot == EquipmentSlot::Melee
+            })
+            .map_or(1, |(_, reach)| reach.range);
+        for step in 1..=reach {
+            let (x, y) = (pos.x + delta_x * step, pos.y + delta_y * step);
+            if x < 1 || x > map.width - 1 || y < 1 || y > map.height - 1 {
+                break;
+            }
+            let idx = map.xy_idx(x, y);
+            if map.tiles[idx] == TileType::Wall {
+                break;
+            }
+
+            for potential_target in map.tile_content[idx].iter() {
+                if *potential_target == entity {
+                    continue;
+                }
+                let target = combat_stats.get(*potential_target);
+                if let Some(_target) = target {
+                    wants_to_melee
+                        .insert(
+                            entity,
+                            WantsToMelee {
+                                target: *potential_target,
+                            },
+                        )
+                        .expect("Add target failed");
+                    return;
+                }
             }
         }
 
         if !map.blocked[destination_idx] {
-            pos.x = min(79, max(0, pos.x + delta_x));
-            pos.y = min(49, max(0, pos.y + delta_y));
+            pos.x = (pos.x + delta_x).clamp(0, 79);
+            pos.y = (pos.y + delta_y).clamp(0, 49);
 
             viewshed.dirty = true;
+            moved
+                .insert(entity, EntityMoved {})
+                .expect("Unable to insert marker");
             let mut ppos = ecs.write_resource::<Point>();
             ppos.x = pos.x;
             ppos.y = pos.y;
@@ -54,7 +106,48 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     }
 }
 
+fn fire_weapon(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let ranged_weapons = ecs.read_storage::<RangedWeapon>();
+    let skills = ecs.read_storage::<Skills>();
+
+    let weapon = (&equipped, &ranged_weapons)
+        .join()
+        .find(|(equipped_by, _)| equipped_by.owner == *player_entity);
+    match weapon {
+        // Skilled archers can reach further
+        Some((_, weapon)) => RunState::ShowFireTargeting {
+            range: weapon.range
+                + skills
+                    .get(*player_entity)
+                    .map_or(0, |s| s.rank(Skill::Ranged)),
+        },
+        None => {
+            ecs.write_resource::<GameLog>()
+                .entries
+                .push("You have no ranged weapon equipped.".to_string());
+            RunState::AwaitingInput
+        }
+    }
+}
+
+fn cast_spell(gs: &mut State, slot: usize) -> RunState {
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    match known_spells(&gs.ecs, player_entity).get(slot) {
+        Some(spell) => gs.use_item(*spell),
+        None => RunState::AwaitingInput,
+    }
+}
+
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
+    // A sleeping or stunned player loses their turn until they recover
+    if player_has_status(&gs.ecs, StatusKind::Asleep)
+        || player_has_status(&gs.ecs, StatusKind::Stunned)
+    {
+        return RunState::PlayerTurn;
+    }
+
     // Player movement
     match ctx.key {
         None => return RunState::AwaitingInput, // Nothing happened
@@ -66,12 +159,36 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
                 }
             }
             VirtualKeyCode::Escape => return RunState::SaveGame,
-            VirtualKeyCode::Space => return skip_turn(&mut gs.ecs),
-            VirtualKeyCode::Numpad5 => return skip_turn(&mut gs.ecs),
+            VirtualKeyCode::Space => return RunState::PlayerTurn,
+            VirtualKeyCode::Numpad5 => return RunState::PlayerTurn,
+            VirtualKeyCode::S => return start_resting(&mut gs.ecs),
             VirtualKeyCode::G => get_item(&mut gs.ecs),
             VirtualKeyCode::I => return RunState::ShowInventory,
             VirtualKeyCode::D => return RunState::ShowDropItem,
             VirtualKeyCode::R => return RunState::ShowRemoveItem,
+            VirtualKeyCode::C => return RunState::ShowCharacterSheet,
+            VirtualKeyCode::E => return RunState::ShowEquipment,
+            VirtualKeyCode::F => return fire_weapon(&mut gs.ecs),
+            VirtualKeyCode::Z => return RunState::ShowSpells,
+
+            // Spell hotkeys
+            VirtualKeyCode::Key1 => return cast_spell(gs, 0),
+            VirtualKeyCode::Key2 => return cast_spell(gs, 1),
+            VirtualKeyCode::Key3 => return cast_spell(gs, 2),
+            VirtualKeyCode::Key4 => return cast_spell(gs, 3),
+            VirtualKeyCode::Key5 => return cast_spell(gs, 4),
+            VirtualKeyCode::Key6 => return cast_spell(gs, 5),
+            VirtualKeyCode::Key7 => return cast_spell(gs, 6),
+            VirtualKeyCode::Key8 => return cast_spell(gs, 7),
+            VirtualKeyCode::Key9 => return cast_spell(gs, 8),
+
+            // Development: pick up edits to the raws without restarting
+            #[cfg(debug_assertions)]
+            VirtualKeyCode::F5 => {
+                super::raws::reload_raws(&mut gs.ecs);
+                return RunState::AwaitingInput;
+            }
+
             VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
                 try_move_player(-1, 0, &mut gs.ecs)
             }
@@ -103,35 +220,107 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
     RunState::PlayerTurn
 }
 
-fn skip_turn(ecs: &mut World) -> RunState {
+/// The name of a monster the player can see, if there is one.
+fn visible_monster(ecs: &World) -> Option<String> {
     let player_entity = ecs.fetch::<Entity>();
-    let viewshed_components = ecs.read_storage::<Viewshed>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
     let monsters = ecs.read_storage::<Monster>();
+    let names = ecs.read_storage::<Name>();
+    let map = ecs.fetch::<Map>();
 
-    let worldmap_resource = ecs.fetch::<Map>();
+    let viewshed = viewsheds.get(*player_entity)?;
+    viewshed.visible_tiles.iter().find_map(|tile| {
+        let idx = map.xy_idx(tile.x, tile.y);
+        map.tile_content[idx]
+            .iter()
+            .filter(|e| monsters.get(**e).is_some())
+            .find_map(|e| names.get(*e).map(|n| n.name.clone()))
+    })
+}
 
-    // if no monsters in sight, heal.const
-    let mut can_heal = true;
-    let viewshed = viewshed_components.get(*player_entity).unwrap();
-    for tile in viewshed.visible_tiles.iter() {
-        let idx = worldmap_resource.xy_idx(tile.x, tile.y);
-        for entity_id in worldmap_resource.tile_content[idx].iter() {
-            if let Some(_monster) = monsters.get(*entity_id) {
-                can_heal = false
-            }
+/// Why the player can't rest right now, if anything stops them.
+fn rest_interruption(ecs: &World) -> Option<String> {
+    let player_entity = ecs.fetch::<Entity>();
+
+    if let Some(monster) = visible_monster(ecs) {
+        return Some(format!("You can't rest with a {} nearby.", monster));
+    }
+
+    let hunger_clocks = ecs.read_storage::<HungerClock>();
+    if let Some(hunger) = hunger_clocks.get(*player_entity) {
+        if matches!(hunger.state, HungerState::Hungry | HungerState::Starving) {
+            return Some("You are too hungry to rest.".to_string());
         }
     }
 
-    if can_heal {
-        let mut health_components = ecs.write_storage::<CombatStats>();
-        if let Some(player_stats) = health_components.get_mut(*player_entity) {
-            player_stats.hp = i32::min(player_stats.hp + 1, player_stats.max_hp);
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    if let Some(stats) = combat_stats.get(*player_entity) {
+        let resting = ecs.read_storage::<Resting>();
+        if resting.get(*player_entity).is_some_and(|r| stats.hp < r.hp) {
+            return Some("You are hurt and stop resting.".to_string());
+        }
+        if stats.hp >= stats.max_hp {
+            return Some("You feel fully rested.".to_string());
         }
     }
 
+    None
+}
+
+/// Starts waiting out the turns until the player is healed or disturbed.
+fn start_resting(ecs: &mut World) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    if let Some(reason) = rest_interruption(ecs) {
+        gamelog.entries.push(reason);
+        return RunState::AwaitingInput;
+    }
+
+    gamelog.entries.push("You settle down to rest.".to_string());
+    let hp = ecs
+        .read_storage::<CombatStats>()
+        .get(player_entity)
+        .map_or(0, |stats| stats.hp);
+    ecs.write_storage::<Resting>()
+        .insert(player_entity, Resting { hp })
+        .expect("Unable to insert resting");
     RunState::PlayerTurn
 }
 
+/// Lets another turn pass while the player rests, stopping when they're healed, something
+/// disturbs them, or a key is pressed.
+pub fn keep_resting(ecs: &mut World, ctx: &Rltk) -> RunState {
+    let reason = if ctx.key.is_some() {
+        Some("You stop resting.".to_string())
+    } else {
+        rest_interruption(ecs)
+    };
+
+    match reason {
+        None => {
+            // Health regained while resting is the new mark to be hurt from
+            let player_entity = *ecs.fetch::<Entity>();
+            let hp = ecs
+                .read_storage::<CombatStats>()
+                .get(player_entity)
+                .map(|stats| stats.hp);
+            if let (Some(hp), Some(resting)) =
+                (hp, ecs.write_storage::<Resting>().get_mut(player_entity))
+            {
+                resting.hp = hp;
+            }
+            RunState::PlayerTurn
+        }
+        Some(reason) => {
+            let player_entity = *ecs.fetch::<Entity>();
+            ecs.write_storage::<Resting>().remove(player_entity);
+            ecs.fetch_mut::<GameLog>().entries.push(reason);
+            RunState::AwaitingInput
+        }
+    }
+}
+
 pub fn try_next_level(ecs: &mut World) -> bool {
     let player_pos = ecs.fetch::<Point>();
     let map = ecs.fetch::<Map>();
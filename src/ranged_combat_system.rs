@@ -0,0 +1,227 @@
+use super::{
+    damage_system::total_damage_taken,
+    durability_system::{armor_worn_by, blunted, wear_from_hit},
+    melee_combat_system::{armor_class, equipment_defense},
+    Ammunition, Attributes, CombatStats, DefenseBonus, Durability, Equipped, GameLog, InBackpack,
+    Map, MeleeWeapon, Name, NaturalAttackDefense, Position, RangedWeapon, Resistances, Skill,
+    Skills, SufferDamage, SufferWear, WantsToShoot,
+};
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, RangedWeapon>,
+        WriteStorage<'a, Ammunition>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, NaturalAttackDefense>,
+        WriteStorage<'a, Skills>,
+        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, Durability>,
+        WriteStorage<'a, SufferWear>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, MeleeWeapon>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut log,
+            mut rng,
+            map,
+            player_entity,
+            mut wants_shoot,
+            names,
+            positions,
+            combat_stats,
+            attributes,
+            equipped,
+            backpack,
+            ranged_weapons,
+            mut ammunition,
+            defense_bonus,
+            natural_attacks,
+            mut skills,
+            mut inflict_damage,
+            durability,
+            mut wear,
+            resistances,
+            melee_weapons,
+        ) = data;
+
+        for (entity, shot, name, pos) in (&entities, &wants_shoot, &names, &positions).join() {
+            let weapon = (&entities, &equipped, &ranged_weapons)
+                .join()
+                .find(|(_, equipped_by, _)| equipped_by.owner == entity)
+                .map(|(item, _, weapon)| (item, weapon.clone()));
+            let (weapon_item, weapon) = match weapon {
+                Some(weapon) => weapon,
+                None => continue,
+            };
+
+            // Skilled archers can reach further
+            let ranged_skill = skills.get(entity).map_or(0, |s| s.rank(Skill::Ranged));
+            let distance =
+                rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), shot.target);
+            if distance > (weapon.range + ranged_skill) as f32 {
+                if entity == *player_entity {
+                    log.entries.push("That is out of range.".to_string());
+                }
+                continue;
+            }
+
+            // Every shot needs a projectile from the backpack
+            let quiver = (&entities, &backpack, &ammunition)
+                .join()
+                .find(|(_, pack, ammo)| pack.owner == entity && ammo.ammo_type == weapon.ammo)
+                .map(|(quiver, _, _)| quiver);
+            let quiver = match quiver {
+                Some(quiver) => quiver,
+                None => {
+                    if entity == *player_entity {
+                        log.entries
+                            .push(format!("You have no {}s left!", weapon.ammo.name()));
+                    }
+                    continue;
+                }
+            };
+
+            let idx = map.xy_idx(shot.target.x, shot.target.y);
+            let target = map.tile_content[idx]
+                .iter()
+                .find(|e| combat_stats.get(**e).is_some_and(|s| s.hp > 0))
+                .copied();
+            let target = match target {
+                Some(target) => target,
+                None => {
+                    // Don't waste a shot on an empty tile
+                    if entity == *player_entity {
+                        log.entries
+                            .push("There is nothing there to shoot at.".to_string());
+                    }
+                    continue;
+                }
+            };
+
+            let ammo = ammunition.get_mut(quiver).unwrap();
+            ammo.count -= 1;
+            if ammo.count < 1 {
+                entities
+                    .delete(quiver)
+                    .expect("Unable to delete ammunition");
+                if entity == *player_entity {
+                    log.entries
+                        .push(format!("You shoot your last {}.", weapon.ammo.name()));
+                }
+            }
+
+            let target_name = names.get(target).unwrap();
+            let target_stats = combat_stats.get(target).unwrap();
+
+            let defense =
+                equipment_defense(target, &entities, &equipped, &defense_bonus, &durability);
+            let armor_class = armor_class(
+                target,
+                target_stats,
+                defense,
+                &natural_attacks,
+                &attributes,
+                &skills,
+            );
+            let armor = armor_worn_by(target, &entities, &equipped, &durability, &melee_weapons);
+
+            // Aim is down to a steady hand; long shots are harder
+            let quickness = attributes.get(entity).map_or(0, |a| a.quickness.bonus());
+            let range_penalty = i32::max(0, distance as i32 - weapon.range / 2);
+            let natural_roll = rng.roll_dice(1, 20);
+            let attack_roll =
+                natural_roll + quickness + weapon.hit_bonus + ranged_skill - range_penalty;
+
+            if natural_roll == 1 {
+                log.entries.push(format!(
+                    "{} fumbles the shot at {}.",
+                    &name.name, &target_name.name
+                ));
+            } else if natural_roll == 20 || attack_roll >= armor_class {
+                let dice = if natural_roll == 20 {
+                    weapon.damage_n_dice * 2
+                } else {
+                    weapon.damage_n_dice
+                };
+                let damage = blunted(
+                    rng.roll_dice(dice, weapon.damage_die_type),
+                    weapon_item,
+                    &durability,
+                );
+                let taken = total_damage_taken(
+                    target,
+                    &[(damage, weapon.damage_type)],
+                    &entities,
+                    &resistances,
+                    &equipped,
+                );
+
+                if natural_roll == 20 {
+                    log.entries.push(format!(
+                        "{} lands a critical shot on {}, for {} hp!",
+                        &name.name, &target_name.name, taken
+                    ));
+                } else {
+                    log.entries.push(format!(
+                        "{} shoots {}, for {} hp.",
+                        &name.name, &target_name.name, taken
+                    ));
+                }
+                let source = names
+                    .get(weapon_item)
+                    .map_or(name.name.clone(), |w| format!("{}'s {}", name.name, w.name));
+                SufferDamage::new_damage(
+                    &mut inflict_damage,
+                    target,
+                    damage,
+                    weapon.damage_type,
+                    &source,
+                    Some(entity),
+                );
+
+                wear_from_hit(&mut wear, &mut rng, Some(weapon_item), &armor);
+
+                if let Some(rank) = Skills::practice_skill(&mut skills, entity, Skill::Ranged) {
+                    if entity == *player_entity {
+                        log.entries
+                            .push(format!("Your ranged skill improves to {}!", rank));
+                    }
+                }
+            } else {
+                log.entries.push(format!(
+                    "{} shoots at {}, but misses.",
+                    &name.name, &target_name.name
+                ));
+
+                if let Some(rank) = Skills::practice_skill(&mut skills, target, Skill::Defense) {
+                    if target == *player_entity {
+                        log.entries
+                            .push(format!("Your defense skill improves to {}!", rank));
+                    }
+                }
+            }
+        }
+
+        wants_shoot.clear();
+    }
+}
@@ -0,0 +1,320 @@
+#[cfg(debug_assertions)]
+use super::GameLog;
+use super::{initiative_system::NORMAL_SPEED, DamageType, Skill};
+use rltk::RGB;
+use serde::Deserialize;
+#[cfg(debug_assertions)]
+use specs::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+const EMBEDDED_MONSTERS: &str = include_str!("../raws/monsters.json");
+#[cfg(debug_assertions)]
+const MONSTERS_PATH: &str = "./raws/monsters.json";
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Raws {
+    pub monsters: Vec<MonsterRaw>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MonsterRaw {
+    pub name: String,
+    pub glyph: char,
+    pub colour: String,
+    pub vision_range: i32,
+    #[serde(default = "normal_speed")]
+    pub speed: i32,
+    pub level: i32,
+    pub stats: MonsterStats,
+    pub attributes: MonsterAttributes,
+    #[serde(default)]
+    pub skills: HashMap<Skill, i32>,
+    #[serde(default)]
+    pub resistances: HashMap<DamageType, i32>,
+    pub natural: Option<MonsterNatural>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MonsterNatural {
+    #[serde(default)]
+    pub armor: i32,
+    pub attacks: Vec<MonsterAttack>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MonsterAttack {
+    pub name: String,
+    #[serde(default)]
+    pub hit_bonus: i32,
+    pub damage: String,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    #[serde(default = "single_strike")]
+    pub strikes: i32,
+}
+
+fn single_strike() -> i32 {
+    1
+}
+
+fn normal_speed() -> i32 {
+    NORMAL_SPEED
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MonsterAttributes {
+    pub might: i32,
+    pub fitness: i32,
+    pub quickness: i32,
+    pub intelligence: i32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MonsterStats {
+    pub max_hp: i32,
+    pub defense: i32,
+    pub power: i32,
+}
+
+/// Validated entity definitions that the spawner builds entities from.
+pub struct RawMaster {
+    raws: Raws,
+    monster_index: HashMap<String, usize>,
+}
+
+impl RawMaster {
+    /// Parses and validates raw definitions, describing the first problem found on failure.
+    pub fn load(data: &str) -> Result<Self, String> {
+        let raws: Raws = serde_json::from_str(data).map_err(|e| e.to_string())?;
+
+        let mut seen = HashSet::new();
+        for monster in raws.monsters.iter() {
+            if monster.name.is_empty() {
+                return Err("monster with an empty name".to_string());
+            }
+            if !seen.insert(monster.name.clone()) {
+                return Err(format!("duplicate monster {}", monster.name));
+            }
+            if RGB::from_hex(&monster.colour).is_err() {
+                return Err(format!(
+                    "{} has invalid colour {}",
+                    monster.name, monster.colour
+                ));
+            }
+            if monster.stats.max_hp < 1 {
+                return Err(format!("{} must have at least 1 hp", monster.name));
+            }
+            if monster.level < 1 {
+                return Err(format!("{} must be at least level 1", monster.name));
+            }
+            if monster.vision_range < 1 {
+                return Err(format!("{} must be able to see", monster.name));
+            }
+            if monster.speed < 1 {
+                return Err(format!("{} must be able to move", monster.name));
+            }
+            for (skill, rank) in monster.skills.iter() {
+                if *rank < 0 {
+                    return Err(format!(
+                        "{}'s {} skill can't be negative",
+                        monster.name,
+                        skill.name()
+                    ));
+                }
+            }
+            for (damage_type, percent) in monster.resistances.iter() {
+                if !(-100..=100).contains(percent) {
+                    return Err(format!(
+                        "{}'s {} resistance must be between -100 and 100",
+                        monster.name,
+                        damage_type.name()
+                    ));
+                }
+            }
+            if let Some(natural) = &monster.natural {
+                if natural.attacks.is_empty() {
+                    return Err(format!("{} has no natural attacks", monster.name));
+                }
+                for attack in natural.attacks.iter() {
+                    if rltk::parse_dice_string(&attack.damage).is_err() {
+                        return Err(format!(
+                            "{}'s {} has invalid damage {}",
+                            monster.name, attack.name, attack.damage
+                        ));
+                    }
+                    if attack.strikes < 1 {
+                        return Err(format!(
+                            "{}'s {} must strike at least once",
+                            monster.name, attack.name
+                        ));
+                    }
+                }
+            }
+        }
+
+        let monster_index = raws
+            .monsters
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.name.clone(), i))
+            .collect();
+
+        Ok(Self {
+            raws,
+            monster_index,
+        })
+    }
+
+    /// The definitions compiled into the binary.
+    pub fn embedded() -> Self {
+        Self::load(EMBEDDED_MONSTERS).expect("Embedded raws are invalid")
+    }
+
+    pub fn monster(&self, name: &str) -> Option<&MonsterRaw> {
+        self.monster_index
+            .get(name)
+            .map(|idx| &self.raws.monsters[*idx])
+    }
+}
+
+/// Re-reads the raws from disk so that subsequent spawns use them. Problems are reported in the
+/// game log and the previous definitions are kept.
+#[cfg(debug_assertions)]
+pub fn reload_raws(ecs: &mut World) {
+    let result = std::fs::read_to_string(MONSTERS_PATH)
+        .map_err(|e| e.to_string())
+        .and_then(|data| RawMaster::load(&data));
+
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    match result {
+        Ok(raws) => {
+            *ecs.write_resource::<RawMaster>() = raws;
+            gamelog
+                .entries
+                .push(format!("Reloaded raws from {}.", MONSTERS_PATH));
+        }
+        Err(e) => gamelog
+            .entries
+            .push(format!("Unable to reload {}: {}", MONSTERS_PATH, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn goblin() -> Value {
+        json!({
+            "name": "Goblin",
+            "glyph": "g",
+            "colour": "#FF0000",
+            "vision_range": 8,
+            "level": 1,
+            "stats": { "max_hp": 16, "defense": 1, "power": 0 },
+            "attributes": { "might": 9, "fitness": 10, "quickness": 13, "intelligence": 8 },
+            "natural": { "attacks": [{ "name": "bite", "damage": "1d4" }] }
+        })
+    }
+
+    fn load_with(change: impl FnOnce(&mut Value)) -> Result<RawMaster, String> {
+        let mut monster = goblin();
+        change(&mut monster);
+        RawMaster::load(&json!({ "monsters": [monster] }).to_string())
+    }
+
+    fn error_with(change: impl FnOnce(&mut Value)) -> String {
+        match load_with(change) {
+            Ok(_) => panic!("invalid raws were accepted"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn bundled_raws_are_valid() {
+        assert!(RawMaster::load(EMBEDDED_MONSTERS).is_ok());
+        assert!(load_with(|_| {}).is_ok());
+    }
+
+    #[test]
+    fn malformed_json_is_rejected() {
+        assert!(RawMaster::load("{ \"monsters\": [").is_err());
+    }
+
+    #[test]
+    fn duplicate_and_unnamed_monsters_are_rejected() {
+        let goblins = json!({ "monsters": [goblin(), goblin()] }).to_string();
+        assert_eq!(
+            RawMaster::load(&goblins).err().unwrap(),
+            "duplicate monster Goblin"
+        );
+        assert_eq!(
+            error_with(|m| m["name"] = json!("")),
+            "monster with an empty name"
+        );
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        assert_eq!(
+            error_with(|m| m["colour"] = json!("red")),
+            "Goblin has invalid colour red"
+        );
+        assert_eq!(
+            error_with(|m| m["stats"]["max_hp"] = json!(0)),
+            "Goblin must have at least 1 hp"
+        );
+        assert_eq!(
+            error_with(|m| m["vision_range"] = json!(0)),
+            "Goblin must be able to see"
+        );
+    }
+
+    #[test]
+    fn monsters_start_at_level_one() {
+        assert_eq!(
+            error_with(|m| m["level"] = json!(0)),
+            "Goblin must be at least level 1"
+        );
+    }
+
+    #[test]
+    fn negative_skills_are_rejected() {
+        assert_eq!(
+            error_with(|m| m["skills"] = json!({ "Melee": -1 })),
+            "Goblin's melee skill can't be negative"
+        );
+    }
+
+    #[test]
+    fn broken_natural_attacks_are_rejected() {
+        assert_eq!(
+            error_with(|m| m["natural"]["attacks"] = json!([])),
+            "Goblin has no natural attacks"
+        );
+        assert_eq!(
+            error_with(|m| m["natural"]["attacks"][0]["damage"] = json!("lots")),
+            "Goblin's bite has invalid damage lots"
+        );
+        assert_eq!(
+            error_with(|m| m["natural"]["attacks"][0]["strikes"] = json!(0)),
+            "Goblin's bite must strike at least once"
+        );
+    }
+
+    #[test]
+    fn resistances_are_limited_to_a_hundred_percent() {
+        assert_eq!(
+            error_with(|m| m["resistances"] = json!({ "Fire": 150 })),
+            "Goblin's fire resistance must be between -100 and 100"
+        );
+    }
+
+    #[test]
+    fn monsters_must_be_able_to_move() {
+        assert_eq!(
+            error_with(|m| m["speed"] = json!(0)),
+            "Goblin must be able to move"
+        );
+    }
+}
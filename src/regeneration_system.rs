@@ -0,0 +1,97 @@
+use super::{
+    initiative_system::TurnClock, Attributes, CombatStats, Equipped, HealthRegenBonus, HungerClock,
+    HungerState, RunState,
+};
+use specs::prelude::*;
+
+const MANA_REGEN_TURNS: i32 = 6;
+
+/// Turns between each hit point regained, before fitness, hunger and equipment are considered.
+const HEALTH_REGEN_TURNS: i32 = 10;
+
+/// How many turns sooner the well fed regain each hit point.
+const WELL_FED_BONUS: i32 = 2;
+
+/// How many turns it takes a creature to regain a hit point, or `None` if they're too hungry to
+/// heal at all.
+pub fn health_regen_interval(
+    entity: Entity,
+    attributes: Option<&Attributes>,
+    hunger: Option<&HungerClock>,
+    equipped: &ReadStorage<Equipped>,
+    regen_bonus: &ReadStorage<HealthRegenBonus>,
+) -> Option<i32> {
+    let hunger_bonus = match hunger.map(|h| h.state) {
+        Some(HungerState::Hungry | HungerState::Starving) => return None,
+        Some(HungerState::WellFed) => WELL_FED_BONUS,
+        _ => 0,
+    };
+    let fitness = attributes.map_or(0, |a| a.fitness.bonus());
+    let equipment_bonus: i32 = (equipped, regen_bonus)
+        .join()
+        .filter(|(e, _)| e.owner == entity)
+        .map(|(_, b)| b.turns)
+        .sum();
+    Some(i32::max(
+        1,
+        HEALTH_REGEN_TURNS - fitness - hunger_bonus - equipment_bonus,
+    ))
+}
+
+/// Restores health and mana over time. The fit heal sooner and the intelligent regain mana
+/// sooner, while the hungry don't heal at all.
+pub struct RegenerationSystem {}
+
+impl<'a> System<'a> for RegenerationSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, TurnClock>,
+        WriteStorage<'a, CombatStats>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, HungerClock>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, HealthRegenBonus>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            runstate,
+            clock,
+            mut combat_stats,
+            attributes,
+            hunger,
+            equipped,
+            regen_bonus,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn || !clock.new_turn() {
+            return;
+        }
+
+        for (entity, stats, attributes) in (&entities, &mut combat_stats, attributes.maybe()).join()
+        {
+            let intelligence = attributes.map_or(0, |a| a.intelligence.bonus());
+            let interval = i32::max(1, MANA_REGEN_TURNS - intelligence);
+            if stats.mana < stats.max_mana && clock.turn() % interval == 0 {
+                stats.mana += 1;
+            }
+
+            if stats.hp < 1 || stats.hp >= stats.max_hp {
+                continue;
+            }
+            let interval = health_regen_interval(
+                entity,
+                attributes,
+                hunger.get(entity),
+                &equipped,
+                &regen_bonus,
+            );
+            if interval.is_some_and(|interval| clock.turn() % interval == 0) {
+                stats.hp += 1;
+            }
+        }
+    }
+}
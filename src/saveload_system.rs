@@ -29,9 +29,28 @@ pub fn save_game(_ecs: &mut World) {}
 pub fn save_game(ecs: &mut World) {
     // Create helper
     let mapcopy = ecs.get_mut::<super::map::Map>().unwrap().clone();
+    let bosses = ecs
+        .get_mut::<super::spawner::SpawnedBosses>()
+        .unwrap()
+        .clone();
+    let identification = ecs.get_mut::<super::ItemIdentification>().unwrap().clone();
+    let turn_clock = ecs
+        .get_mut::<super::initiative_system::TurnClock>()
+        .unwrap()
+        .clone();
+    let damage_history = ecs
+        .get_mut::<super::damage_system::DamageHistory>()
+        .unwrap()
+        .clone();
     let savehelper = ecs
         .create_entity()
-        .with(SerializationHelper { map: mapcopy })
+        .with(SerializationHelper {
+            map: mapcopy,
+            spawned_bosses: bosses,
+            identification,
+            turn_clock,
+            damage_history,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
@@ -56,14 +75,22 @@ pub fn save_game(ecs: &mut World) {
             Name,
             BlocksTile,
             CombatStats,
-            SufferDamage,
             WantsToMelee,
             Item,
             Consumable,
             Ranged,
             InflictsDamage,
             AreaOfEffect,
-            Confusion,
+            InflictsStatus,
+            StatusEffect,
+            Energy,
+            SpeedPenalty,
+            RangedWeapon,
+            Ammunition,
+            WantsToShoot,
+            Spell,
+            InSpellbook,
+            TeachesSpell,
             ProvidesHealing,
             InBackpack,
             WantsToPickupItem,
@@ -73,8 +100,38 @@ pub fn save_game(ecs: &mut World) {
             Equippable,
             Equipped,
             MeleePowerBonus,
+            HealthRegenBonus,
             DefenseBonus,
-            WantsToRemoveItem
+            WantsToRemoveItem,
+            Boss,
+            BonusDamage,
+            CriticalHit,
+            WeaponProc,
+            TwoHanded,
+            OffHand,
+            Reach,
+            Durability,
+            SufferWear,
+            RepairsItems,
+            HungerClock,
+            ProvidesFood,
+            Hidden,
+            EntryTrigger,
+            EntityMoved,
+            SingleActivation,
+            TeleportsTarget,
+            Knockback,
+            PullsTarget,
+            MagicItem,
+            ProvidesIdentification,
+            Cursed,
+            RemovesCurse,
+            Attributes,
+            MeleeWeapon,
+            Experience,
+            Skills,
+            NaturalAttackDefense,
+            Resistances
         );
     }
 
@@ -135,14 +192,22 @@ pub fn load_game(ecs: &mut World) {
             Name,
             BlocksTile,
             CombatStats,
-            SufferDamage,
             WantsToMelee,
             Item,
             Consumable,
             Ranged,
             InflictsDamage,
             AreaOfEffect,
-            Confusion,
+            InflictsStatus,
+            StatusEffect,
+            Energy,
+            SpeedPenalty,
+            RangedWeapon,
+            Ammunition,
+            WantsToShoot,
+            Spell,
+            InSpellbook,
+            TeachesSpell,
             ProvidesHealing,
             InBackpack,
             WantsToPickupItem,
@@ -152,8 +217,38 @@ pub fn load_game(ecs: &mut World) {
             Equippable,
             Equipped,
             MeleePowerBonus,
+            HealthRegenBonus,
             DefenseBonus,
-            WantsToRemoveItem
+            WantsToRemoveItem,
+            Boss,
+            BonusDamage,
+            CriticalHit,
+            WeaponProc,
+            TwoHanded,
+            OffHand,
+            Reach,
+            Durability,
+            SufferWear,
+            RepairsItems,
+            HungerClock,
+            ProvidesFood,
+            Hidden,
+            EntryTrigger,
+            EntityMoved,
+            SingleActivation,
+            TeleportsTarget,
+            Knockback,
+            PullsTarget,
+            MagicItem,
+            ProvidesIdentification,
+            Cursed,
+            RemovesCurse,
+            Attributes,
+            MeleeWeapon,
+            Experience,
+            Skills,
+            NaturalAttackDefense,
+            Resistances
         );
     }
 
@@ -167,6 +262,12 @@ pub fn load_game(ecs: &mut World) {
             let mut worldmap = ecs.write_resource::<super::map::Map>();
             *worldmap = h.map.clone();
             worldmap.tile_content = vec![Vec::new(); super::map::MAPCOUNT];
+            let mut bosses = ecs.write_resource::<super::spawner::SpawnedBosses>();
+            *bosses = h.spawned_bosses.clone();
+            let mut identification = ecs.write_resource::<super::ItemIdentification>();
+            *identification = h.identification.clone();
+            *ecs.write_resource::<super::initiative_system::TurnClock>() = h.turn_clock.clone();
+            *ecs.write_resource::<super::damage_system::DamageHistory>() = h.damage_history.clone();
             deleteme = Some(e);
         }
         for (e, _p, pos) in (&entities, &player, &position).join() {
@@ -1,16 +1,304 @@
 use super::{
-    AreaOfEffect, BlocksTile, CombatStats, Confusion, Consumable, DefenseBonus, EquipmentSlot,
-    Equippable, InflictsDamage, Item, MeleePowerBonus, Monster, Name, Player, Position,
-    ProvidesHealing, RandomTable, Ranged, Rect, Renderable, SerializeMe, Viewshed, MAPWIDTH,
+    hunger_system::HUNGER_TURNS,
+    initiative_system::{ACTION_COST, NORMAL_SPEED},
+    item_generator, AmmoType, Ammunition, AreaOfEffect, Attributes, BlocksTile, Boss, BossAbility,
+    CombatStats, Consumable, CriticalHit, DamageType, DefenseBonus, Durability, Energy,
+    EntryTrigger, EquipmentSlot, Equippable, Experience, HealthRegenBonus, Hidden, HungerClock,
+    HungerState, InSpellbook, InflictsDamage, InflictsStatus, Item, ItemIdentification, Knockback,
+    Map, MeleePowerBonus, MeleeWeapon, Monster, Name, NaturalAttack, NaturalAttackDefense, OffHand,
+    Player, Position, ProvidesFood, ProvidesHealing, ProvidesIdentification, PullsTarget,
+    RandomTable, Ranged, RangedWeapon, RawMaster, Reach, Rect, RemovesCurse, Renderable,
+    RepairsItems, Resistances, SerializeMe, SingleActivation, Skill, Skills, SpeedPenalty, Spell,
+    StatusKind, TeachesSpell, TeleportsTarget, TileType, TwoHanded, Viewshed, WeaponProc, MAPWIDTH,
 };
 use rltk::{RandomNumberGenerator, RGB};
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 const MAX_SPAWNS: i32 = 7;
+const EQUIPMENT_DURABILITY: i32 = 40;
 
-fn longsword(ecs: &mut World, x: i32, y: i32) {
+/// Names of the bosses that have already been spawned this run, so that they never respawn.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct SpawnedBosses {
+    pub names: HashSet<String>,
+}
+
+struct BossTemplate {
+    name: &'static str,
+    depth: i32,
+    glyph: char,
+    colour: (u8, u8, u8),
+    max_hp: i32,
+    defense: i32,
+    power: i32,
+    attributes: Attributes,
+    level: i32,
+    skills: Vec<(Skill, i32)>,
+    abilities: Vec<BossAbility>,
+    loot: Vec<&'static str>,
+}
+
+fn boss_table() -> Vec<BossTemplate> {
+    vec![
+        BossTemplate {
+            name: "Grak the Goblin King",
+            depth: 3,
+            glyph: 'G',
+            colour: rltk::GOLD,
+            max_hp: 40,
+            defense: 2,
+            power: 2,
+            attributes: Attributes::new(14, 13, 12, 9),
+            level: 5,
+            skills: vec![(Skill::Melee, 2), (Skill::Defense, 1)],
+            abilities: vec![BossAbility::Enrage {
+                bonus: 3,
+                triggered: false,
+            }],
+            loot: vec!["Longsword", "Health Potion"],
+        },
+        BossTemplate {
+            name: "Ulfang the Orc Warlord",
+            depth: 6,
+            glyph: 'O',
+            colour: rltk::ORANGE_RED,
+            max_hp: 70,
+            defense: 4,
+            power: 3,
+            attributes: Attributes::new(16, 15, 10, 8),
+            level: 8,
+            skills: vec![(Skill::Melee, 3), (Skill::Defense, 2)],
+            abilities: vec![
+                BossAbility::Regeneration { amount: 2 },
+                BossAbility::Enrage {
+                    bonus: 4,
+                    triggered: false,
+                },
+            ],
+            loot: vec!["Tower Shield", "Fireball Scroll", "Health Potion"],
+        },
+    ]
+}
+
+/// Spawns the boss configured for this map's depth on top of the downstairs, unless it has already
+/// been spawned earlier in the run.
+pub fn spawn_boss(ecs: &mut World, map: &Map) {
+    let template = match boss_table().into_iter().find(|b| b.depth == map.depth) {
+        Some(template) => template,
+        None => return,
+    };
+
+    let stairs_idx = match map.tiles.iter().position(|t| *t == TileType::Downstairs) {
+        Some(idx) => idx as i32,
+        None => return,
+    };
+    let x = stairs_idx % map.width;
+    let y = stairs_idx / map.width;
+
+    {
+        let mut spawned = ecs.write_resource::<SpawnedBosses>();
+        if !spawned.names.insert(template.name.to_string()) {
+            return;
+        }
+    }
+
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(template.glyph),
+            fg: RGB::named(template.colour),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 1,
+        })
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true,
+        })
+        .with(Monster {})
+        .with(Boss {
+            abilities: template.abilities,
+            loot: template.loot.iter().map(|s| s.to_string()).collect(),
+        })
+        .with(Name {
+            name: template.name.to_string(),
+        })
+        .with(template.attributes)
+        .with(Skills {
+            ranks: template.skills.into_iter().collect(),
+            practice: HashMap::new(),
+        })
+        .with(Energy {
+            speed: NORMAL_SPEED,
+            current: 0,
+        })
+        .with(Experience {
+            level: template.level,
+            xp: 0,
+        })
+        .with(BlocksTile {})
+        .with(CombatStats {
+            max_hp: template.max_hp,
+            hp: template.max_hp,
+            max_mana: 0,
+            mana: 0,
+            defense: template.defense,
+            power: template.power,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn shortbow(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('}'),
+            fg: RGB::named(rltk::CYAN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Shortbow".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Ranged,
+        })
+        .with(RangedWeapon {
+            range: 8,
+            damage_n_dice: 1,
+            damage_die_type: 6,
+            damage_type: DamageType::Piercing,
+            hit_bonus: 0,
+            ammo: AmmoType::Arrow,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn crossbow(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('}'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Crossbow".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Ranged,
+        })
+        .with(RangedWeapon {
+            range: 10,
+            damage_n_dice: 1,
+            damage_die_type: 10,
+            damage_type: DamageType::Piercing,
+            hit_bonus: 1,
+            ammo: AmmoType::Bolt,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn spellbook(ecs: &mut World, x: i32, y: i32, spell: &str) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('?'),
+            fg: RGB::named(rltk::MEDIUM_PURPLE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: format!("{} Spellbook", spell),
+        })
+        .with(Item {})
+        .with(Consumable::default())
+        .with(TeachesSpell {
+            spell: spell.to_string(),
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// Adds a spell to the owner's spellbook. Spells are described like the items that share their
+/// effects, with a mana cost instead of being consumed.
+pub fn spell(ecs: &mut World, name: &str, owner: Entity) {
+    let builder = ecs
+        .create_entity()
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(InSpellbook { owner });
+
+    let builder = match name {
+        "Magic Missile" => builder
+            .with(Spell { mana_cost: 2 })
+            .with(Ranged { range: 6 })
+            .with(InflictsDamage {
+                damage: 6,
+                damage_type: DamageType::Magic,
+            }),
+        "Fireball" => builder
+            .with(Spell { mana_cost: 6 })
+            .with(Ranged { range: 6 })
+            .with(AreaOfEffect { radius: 2 })
+            .with(InflictsDamage {
+                damage: 12,
+                damage_type: DamageType::Fire,
+            }),
+        "Confuse" => builder
+            .with(Spell { mana_cost: 3 })
+            .with(Ranged { range: 6 })
+            .with(InflictsStatus {
+                kind: StatusKind::Confused,
+                duration: 4,
+                magnitude: 0,
+            }),
+        "Force Bolt" => builder
+            .with(Spell { mana_cost: 3 })
+            .with(Ranged { range: 6 })
+            .with(InflictsDamage {
+                damage: 4,
+                damage_type: DamageType::Magic,
+            })
+            .with(Knockback { distance: 3 }),
+        "Mend" => builder
+            .with(Spell { mana_cost: 4 })
+            .with(ProvidesHealing { heal_amount: 8 }),
+        _ => return,
+    };
+
+    builder.marked::<SimpleMarker<SerializeMe>>().build();
+}
+
+fn ammunition(ecs: &mut World, x: i32, y: i32, name: &str, ammo_type: AmmoType, count: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('|'),
+            fg: RGB::named(rltk::BURLYWOOD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Item {})
+        .with(Ammunition { ammo_type, count })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn longsword(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -26,12 +314,17 @@ fn longsword(ecs: &mut World, x: i32, y: i32) {
         .with(Equippable {
             slot: EquipmentSlot::Melee,
         })
-        .with(MeleePowerBonus { power: 4 })
+        .with(MeleeWeapon {
+            damage_n_dice: 1,
+            damage_die_type: 8,
+            damage_type: DamageType::Slashing,
+            hit_bonus: 0,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn tower_shield(ecs: &mut World, x: i32, y: i32) {
+fn tower_shield(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -48,11 +341,15 @@ fn tower_shield(ecs: &mut World, x: i32, y: i32) {
             slot: EquipmentSlot::Shield,
         })
         .with(DefenseBonus { defense: 3 })
+        .with(SpeedPenalty { penalty: 20 })
+        .with(Resistances {
+            percent: HashMap::from([(DamageType::Fire, 25)]),
+        })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn dagger(ecs: &mut World, x: i32, y: i32) {
+fn dagger(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -68,12 +365,168 @@ fn dagger(ecs: &mut World, x: i32, y: i32) {
         .with(Equippable {
             slot: EquipmentSlot::Melee,
         })
-        .with(MeleePowerBonus { power: 2 })
+        .with(MeleeWeapon {
+            damage_n_dice: 1,
+            damage_die_type: 4,
+            damage_type: DamageType::Piercing,
+            hit_bonus: 1,
+        })
+        .with(CriticalHit {
+            threat: 19,
+            multiplier: 2,
+        })
+        .with(OffHand {})
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
+}
+
+fn mace(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::LIGHT_GREY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Mace".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(MeleeWeapon {
+            damage_n_dice: 1,
+            damage_die_type: 6,
+            damage_type: DamageType::Bludgeoning,
+            hit_bonus: 0,
+        })
+        .with(WeaponProc {
+            kind: StatusKind::Stunned,
+            chance: 15,
+            duration: 1,
+            magnitude: 0,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
 }
 
-fn shield(ecs: &mut World, x: i32, y: i32) {
+fn battle_axe(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Battle Axe".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(MeleeWeapon {
+            damage_n_dice: 1,
+            damage_die_type: 8,
+            damage_type: DamageType::Slashing,
+            hit_bonus: -1,
+        })
+        .with(CriticalHit {
+            threat: 20,
+            multiplier: 3,
+        })
+        .with(TwoHanded {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn warhammer(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::SLATE_GRAY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Warhammer".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(MeleeWeapon {
+            damage_n_dice: 1,
+            damage_die_type: 10,
+            damage_type: DamageType::Bludgeoning,
+            hit_bonus: -1,
+        })
+        .with(TwoHanded {})
+        .with(Knockback { distance: 2 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn greatsword(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::WHITE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Greatsword".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(MeleeWeapon {
+            damage_n_dice: 2,
+            damage_die_type: 6,
+            damage_type: DamageType::Slashing,
+            hit_bonus: 0,
+        })
+        .with(TwoHanded {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn spear(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::BROWN1),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Spear".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(MeleeWeapon {
+            damage_n_dice: 1,
+            damage_die_type: 8,
+            damage_type: DamageType::Piercing,
+            hit_bonus: 0,
+        })
+        .with(TwoHanded {})
+        .with(Reach { range: 2 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn shield(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -91,21 +544,115 @@ fn shield(ecs: &mut World, x: i32, y: i32) {
         })
         .with(DefenseBonus { defense: 1 })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
+}
+
+/// The parts shared by everything that can be worn, ready for the item's own bonuses.
+fn wearable<'a>(
+    ecs: &'a mut World,
+    x: i32,
+    y: i32,
+    name: &str,
+    glyph: char,
+    slot: EquipmentSlot,
+) -> EntityBuilder<'a> {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(glyph),
+            fg: RGB::named(rltk::CYAN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Item {})
+        .with(Equippable { slot })
+        .marked::<SimpleMarker<SerializeMe>>()
+}
+
+/// Heavier armor protects more, but slows its wearer down.
+fn armor(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    name: &str,
+    slot: EquipmentSlot,
+    defense: i32,
+    speed_penalty: i32,
+) -> Entity {
+    let builder = wearable(ecs, x, y, name, '[', slot).with(DefenseBonus { defense });
+    if speed_penalty > 0 {
+        builder
+            .with(SpeedPenalty {
+                penalty: speed_penalty,
+            })
+            .build()
+    } else {
+        builder.build()
+    }
 }
 
 fn room_table(map_depth: i32) -> RandomTable {
     RandomTable::new()
         .add("Goblin", 10)
         .add("Orc", 1 + map_depth)
+        .add("Wolf", map_depth)
+        .add("Bat", 3 + map_depth / 2)
+        .add("Zombie", map_depth - 1)
+        .add("Bear", map_depth - 2)
+        .add("Fire Elemental", map_depth - 3)
         .add("Health Potion", 7)
         .add("Fireball Scroll", 2 + map_depth)
         .add("Confusion Scroll", 2 + map_depth)
+        .add("Sleep Scroll", 1 + map_depth / 2)
+        .add("Teleport Scroll", 1 + map_depth / 3)
+        .add("Poison Potion", 2)
+        .add("Haste Potion", 2)
         .add("Magic Missle Scroll", 4)
+        .add("Identify Scroll", 2)
+        .add("Remove Curse Scroll", 1 + map_depth / 2)
+        .add("Repair Kit", 2 + map_depth / 2)
+        .add("Grappling Hook", 1)
+        .add("Ration", 6)
+        .add("Bear Trap", 2)
+        .add("Pit", 2)
+        .add("Gas Trap", map_depth - 1)
+        .add("Teleport Trap", map_depth - 2)
         .add("Dagger", 3)
         .add("Shield", 3)
         .add("Longsword", map_depth - 1)
+        .add("Mace", map_depth - 1)
+        .add("Battle Axe", map_depth - 2)
+        .add("Greatsword", map_depth - 3)
+        .add("Warhammer", map_depth - 3)
+        .add("Spear", 2)
         .add("Tower Shield", map_depth - 1)
+        .add("Leather Cap", 2)
+        .add("Leather Armor", 2)
+        .add("Leather Leggings", 2)
+        .add("Leather Boots", 2)
+        .add("Leather Gloves", 2)
+        .add("Iron Helm", map_depth - 1)
+        .add("Chain Mail", map_depth - 2)
+        .add("Iron Greaves", map_depth - 2)
+        .add("Iron Boots", map_depth - 2)
+        .add("Gauntlets", map_depth - 2)
+        .add("Plate Armor", map_depth - 4)
+        .add("Ring of Protection", map_depth / 2)
+        .add("Ring of Power", map_depth / 2)
+        .add("Ring of Regeneration", map_depth / 3)
+        .add("Amulet of Fire Warding", map_depth / 3)
+        .add("Amulet of Frost Warding", map_depth / 3)
+        .add("Shortbow", 2)
+        .add("Crossbow", map_depth - 2)
+        .add("Fireball Spellbook", map_depth / 3)
+        .add("Confuse Spellbook", 1)
+        .add("Mend Spellbook", 1)
+        .add("Force Bolt Spellbook", map_depth / 2)
+        .add("Arrows", 4)
+        .add("Bolts", map_depth - 2)
 }
 
 pub fn spawn_room(ecs: &mut World, room: &Rect, map_depth: i32) {
@@ -124,8 +671,8 @@ pub fn spawn_room(ecs: &mut World, room: &Rect, map_depth: i32) {
                 let y = (room.y1 + rng.roll_dice(1, i32::abs(room.y2 - room.y1))) as usize;
 
                 let idx = (y * MAPWIDTH) + x;
-                if !spawn_points.contains_key(&idx) {
-                    spawn_points.insert(idx, spawn_table.roll(&mut rng));
+                if let Entry::Vacant(e) = spawn_points.entry(idx) {
+                    e.insert(spawn_table.roll(&mut rng));
                     added = true;
                 } else {
                     tries += 1;
@@ -139,28 +686,114 @@ pub fn spawn_room(ecs: &mut World, room: &Rect, map_depth: i32) {
         let x = (*index % MAPWIDTH) as i32;
         let y = (*index / MAPWIDTH) as i32;
 
-        match item {
-            "Goblin" => goblin(ecs, x, y),
-            "Orc" => orc(ecs, x, y),
-            "Health Potion" => health_potion(ecs, x, y),
-            "Fireball Scroll" => fireball_scroll(ecs, x, y),
-            "Confusion Scroll" => confusion_scroll(ecs, x, y),
-            "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
-            "Dagger" => dagger(ecs, x, y),
-            "Shield" => shield(ecs, x, y),
-            "Longsword" => longsword(ecs, x, y),
-            "Tower Shield" => tower_shield(ecs, x, y),
-            _ => {}
+        spawn_named(ecs, item, x, y, map_depth);
+    }
+}
+
+pub fn spawn_named(ecs: &mut World, name: &str, x: i32, y: i32, map_depth: i32) {
+    let equipment = match name {
+        "Dagger" => dagger(ecs, x, y),
+        "Shield" => shield(ecs, x, y),
+        "Longsword" => longsword(ecs, x, y),
+        "Mace" => mace(ecs, x, y),
+        "Battle Axe" => battle_axe(ecs, x, y),
+        "Greatsword" => greatsword(ecs, x, y),
+        "Warhammer" => warhammer(ecs, x, y),
+        "Spear" => spear(ecs, x, y),
+        "Tower Shield" => tower_shield(ecs, x, y),
+        "Leather Cap" => armor(ecs, x, y, name, EquipmentSlot::Head, 1, 0),
+        "Iron Helm" => armor(ecs, x, y, name, EquipmentSlot::Head, 2, 0),
+        "Leather Armor" => armor(ecs, x, y, name, EquipmentSlot::Body, 1, 0),
+        "Chain Mail" => armor(ecs, x, y, name, EquipmentSlot::Body, 3, 10),
+        "Plate Armor" => armor(ecs, x, y, name, EquipmentSlot::Body, 5, 30),
+        "Leather Leggings" => armor(ecs, x, y, name, EquipmentSlot::Legs, 1, 0),
+        "Iron Greaves" => armor(ecs, x, y, name, EquipmentSlot::Legs, 2, 10),
+        "Leather Boots" => armor(ecs, x, y, name, EquipmentSlot::Feet, 1, 0),
+        "Iron Boots" => armor(ecs, x, y, name, EquipmentSlot::Feet, 2, 10),
+        "Leather Gloves" => armor(ecs, x, y, name, EquipmentSlot::Hands, 1, 0),
+        "Gauntlets" => armor(ecs, x, y, name, EquipmentSlot::Hands, 2, 0),
+        "Ring of Protection" => wearable(ecs, x, y, name, '=', EquipmentSlot::Ring)
+            .with(DefenseBonus { defense: 1 })
+            .build(),
+        "Ring of Power" => wearable(ecs, x, y, name, '=', EquipmentSlot::Ring)
+            .with(MeleePowerBonus { power: 1 })
+            .build(),
+        "Ring of Regeneration" => wearable(ecs, x, y, name, '=', EquipmentSlot::Ring)
+            .with(HealthRegenBonus { turns: 4 })
+            .build(),
+        "Amulet of Fire Warding" => wearable(ecs, x, y, name, '"', EquipmentSlot::Amulet)
+            .with(Resistances {
+                percent: HashMap::from([(DamageType::Fire, 50)]),
+            })
+            .build(),
+        "Amulet of Frost Warding" => wearable(ecs, x, y, name, '"', EquipmentSlot::Amulet)
+            .with(Resistances {
+                percent: HashMap::from([(DamageType::Cold, 50)]),
+            })
+            .build(),
+        "Shortbow" => shortbow(ecs, x, y),
+        "Crossbow" => crossbow(ecs, x, y),
+        _ => {
+            match name {
+                "Health Potion" => health_potion(ecs, x, y),
+                "Fireball Scroll" => fireball_scroll(ecs, x, y),
+                "Confusion Scroll" => confusion_scroll(ecs, x, y),
+                "Sleep Scroll" => sleep_scroll(ecs, x, y),
+                "Teleport Scroll" => teleport_scroll(ecs, x, y),
+                "Poison Potion" => poison_potion(ecs, x, y),
+                "Haste Potion" => haste_potion(ecs, x, y),
+                "Fireball Spellbook" => spellbook(ecs, x, y, "Fireball"),
+                "Confuse Spellbook" => spellbook(ecs, x, y, "Confuse"),
+                "Mend Spellbook" => spellbook(ecs, x, y, "Mend"),
+                "Force Bolt Spellbook" => spellbook(ecs, x, y, "Force Bolt"),
+                "Arrows" => ammunition(ecs, x, y, "Arrows", AmmoType::Arrow, 20),
+                "Bolts" => ammunition(ecs, x, y, "Bolts", AmmoType::Bolt, 12),
+                "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
+                "Identify Scroll" => identify_scroll(ecs, x, y),
+                "Remove Curse Scroll" => remove_curse_scroll(ecs, x, y),
+                "Repair Kit" => repair_kit(ecs, x, y),
+                "Grappling Hook" => grappling_hook(ecs, x, y),
+                "Ration" => ration(ecs, x, y),
+                "Bear Trap" => bear_trap(ecs, x, y),
+                "Pit" => pit(ecs, x, y),
+                "Teleport Trap" => teleport_trap(ecs, x, y),
+                "Gas Trap" => gas_trap(ecs, x, y),
+                _ => monster(ecs, x, y, name),
+            }
+            return;
         }
+    };
+
+    // Everything but jewelry wears out with use
+    let slot = ecs
+        .read_storage::<Equippable>()
+        .get(equipment)
+        .map(|e| e.slot);
+    if !matches!(slot, Some(EquipmentSlot::Ring | EquipmentSlot::Amulet)) {
+        ecs.write_storage::<Durability>()
+            .insert(
+                equipment,
+                Durability {
+                    max: EQUIPMENT_DURABILITY,
+                    current: EQUIPMENT_DURABILITY,
+                },
+            )
+            .expect("Unable to insert durability");
     }
+
+    // Equipment may roll a rarity and pick up affixes
+    item_generator::roll_magic_item(ecs, equipment, map_depth);
 }
 
 fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
+    let fg = ecs
+        .fetch::<ItemIdentification>()
+        .colour("Magic Missile Scroll");
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
             glyph: rltk::to_cp437(')'),
-            fg: RGB::named(rltk::CYAN),
+            fg,
             bg: RGB::named(rltk::BLACK),
             render_order: 2,
         })
@@ -170,17 +803,21 @@ fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Consumable::default())
         .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 8 })
+        .with(InflictsDamage {
+            damage: 8,
+            damage_type: DamageType::Magic,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
 fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
+    let fg = ecs.fetch::<ItemIdentification>().colour("Fireball Scroll");
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
             glyph: rltk::to_cp437(')'),
-            fg: RGB::named(rltk::ORANGE),
+            fg,
             bg: RGB::named(rltk::BLACK),
             render_order: 2,
         })
@@ -190,18 +827,22 @@ fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Consumable::default())
         .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 20 })
+        .with(InflictsDamage {
+            damage: 20,
+            damage_type: DamageType::Fire,
+        })
         .with(AreaOfEffect { radius: 3 })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
 fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
+    let fg = ecs.fetch::<ItemIdentification>().colour("Confusion Scroll");
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
             glyph: rltk::to_cp437(')'),
-            fg: RGB::named(rltk::PINK),
+            fg,
             bg: RGB::named(rltk::BLACK),
             render_order: 2,
         })
@@ -211,17 +852,250 @@ fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Consumable::default())
         .with(Ranged { range: 6 })
-        .with(Confusion { turns: 4 })
+        .with(InflictsStatus {
+            kind: StatusKind::Confused,
+            duration: 4,
+            magnitude: 0,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn teleport_scroll(ecs: &mut World, x: i32, y: i32) {
+    let fg = ecs.fetch::<ItemIdentification>().colour("Teleport Scroll");
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Teleport Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable::default())
+        .with(TeleportsTarget {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn sleep_scroll(ecs: &mut World, x: i32, y: i32) {
+    let fg = ecs.fetch::<ItemIdentification>().colour("Sleep Scroll");
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Sleep Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable::default())
+        .with(Ranged { range: 6 })
+        .with(AreaOfEffect { radius: 2 })
+        .with(InflictsStatus {
+            kind: StatusKind::Asleep,
+            duration: 6,
+            magnitude: 0,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn identify_scroll(ecs: &mut World, x: i32, y: i32) {
+    let fg = ecs.fetch::<ItemIdentification>().colour("Identify Scroll");
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Identify Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable::default())
+        .with(ProvidesIdentification {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// The parts shared by every trap: hidden until spotted, and fired by stepping on it.
+fn trap<'a>(ecs: &'a mut World, x: i32, y: i32, name: &str) -> EntityBuilder<'a> {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('^'),
+            fg: RGB::named(rltk::RED),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Hidden {})
+        .with(EntryTrigger {})
+        .marked::<SimpleMarker<SerializeMe>>()
+}
+
+fn bear_trap(ecs: &mut World, x: i32, y: i32) {
+    trap(ecs, x, y, "Bear Trap")
+        .with(InflictsDamage {
+            damage: 6,
+            damage_type: DamageType::Piercing,
+        })
+        .with(InflictsStatus {
+            kind: StatusKind::Slowed,
+            duration: 5,
+            magnitude: 0,
+        })
+        .build();
+}
+
+fn pit(ecs: &mut World, x: i32, y: i32) {
+    trap(ecs, x, y, "Pit")
+        .with(InflictsDamage {
+            damage: 4,
+            damage_type: DamageType::Bludgeoning,
+        })
+        .with(InflictsStatus {
+            kind: StatusKind::Stunned,
+            duration: 1,
+            magnitude: 0,
+        })
+        .build();
+}
+
+fn teleport_trap(ecs: &mut World, x: i32, y: i32) {
+    trap(ecs, x, y, "Teleport Trap")
+        .with(TeleportsTarget {})
+        .build();
+}
+
+fn gas_trap(ecs: &mut World, x: i32, y: i32) {
+    trap(ecs, x, y, "Gas Trap")
+        .with(InflictsStatus {
+            kind: StatusKind::Confused,
+            duration: 4,
+            magnitude: 0,
+        })
+        .with(SingleActivation {})
+        .build();
+}
+
+fn repair_kit(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('&'),
+            fg: RGB::named(rltk::BROWN1),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Repair Kit".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable::default())
+        .with(RepairsItems {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn grappling_hook(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('¬'),
+            fg: RGB::named(rltk::GRAY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Grappling Hook".to_string(),
+        })
+        .with(Item {})
+        .with(Ranged { range: 6 })
+        .with(PullsTarget { distance: 6 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn remove_curse_scroll(ecs: &mut World, x: i32, y: i32) {
+    let fg = ecs
+        .fetch::<ItemIdentification>()
+        .colour("Remove Curse Scroll");
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Remove Curse Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable::default())
+        .with(RemovesCurse {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn ration(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('%'),
+            fg: RGB::named(rltk::GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Ration".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable::default())
+        .with(ProvidesFood {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// What's left of a slain monster. It makes for a poor meal, but it's better than starving.
+pub fn corpse(ecs: &mut World, x: i32, y: i32, monster_name: &str) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('%'),
+            fg: RGB::named(rltk::DARK_RED),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name {
+            name: format!("{} Corpse", monster_name),
+        })
+        .with(Item {})
+        .with(Consumable::default())
+        .with(ProvidesFood {})
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
 fn health_potion(ecs: &mut World, x: i32, y: i32) {
+    let fg = ecs.fetch::<ItemIdentification>().colour("Health Potion");
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
             glyph: rltk::to_cp437('¡'),
-            fg: RGB::named(rltk::MAGENTA),
+            fg,
             bg: RGB::named(rltk::BLACK),
             render_order: 2,
         })
@@ -235,45 +1109,148 @@ fn health_potion(ecs: &mut World, x: i32, y: i32) {
         .build();
 }
 
-fn orc(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('o'), "Orc");
+fn poison_potion(ecs: &mut World, x: i32, y: i32) {
+    let fg = ecs.fetch::<ItemIdentification>().colour("Poison Potion");
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('¡'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Poison Potion".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable::default())
+        .with(InflictsStatus {
+            kind: StatusKind::Poisoned,
+            duration: 5,
+            magnitude: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
 }
 
-fn goblin(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('g'), "Goblin");
+fn haste_potion(ecs: &mut World, x: i32, y: i32) {
+    let fg = ecs.fetch::<ItemIdentification>().colour("Haste Potion");
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('¡'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Haste Potion".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable::default())
+        .with(InflictsStatus {
+            kind: StatusKind::Hasted,
+            duration: 10,
+            magnitude: 0,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
 }
 
-fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharType, name: S) {
-    ecs.create_entity()
+fn monster(ecs: &mut World, x: i32, y: i32, name: &str) {
+    let template = match ecs.fetch::<RawMaster>().monster(name) {
+        Some(template) => template.clone(),
+        None => return,
+    };
+
+    // Monsters start at a random point in their turn so they don't all move in lockstep
+    let initiative = ecs
+        .write_resource::<RandomNumberGenerator>()
+        .range(0, ACTION_COST);
+
+    let entity = ecs
+        .create_entity()
         .with(Position { x, y })
         .with(Renderable {
-            glyph,
-            fg: RGB::named(rltk::RED),
+            glyph: rltk::to_cp437(template.glyph),
+            fg: RGB::from_hex(&template.colour).expect("Invalid colour"),
             bg: RGB::named(rltk::BLACK),
             render_order: 1,
         })
         .with(Viewshed {
             visible_tiles: Vec::new(),
-            range: 8,
+            range: template.vision_range,
             dirty: true,
         })
         .with(Monster {})
         .with(Name {
-            name: name.to_string(),
+            name: template.name,
+        })
+        .with(Attributes::new(
+            template.attributes.might,
+            template.attributes.fitness,
+            template.attributes.quickness,
+            template.attributes.intelligence,
+        ))
+        .with(Skills {
+            ranks: template.skills,
+            practice: HashMap::new(),
+        })
+        .with(Resistances {
+            percent: template.resistances,
+        })
+        .with(Energy {
+            speed: template.speed,
+            current: initiative,
+        })
+        .with(Experience {
+            level: template.level,
+            xp: 0,
         })
         .with(BlocksTile {})
         .with(CombatStats {
-            max_hp: 16,
-            hp: 16,
-            defense: 1,
-            power: 4,
+            max_hp: template.stats.max_hp,
+            hp: template.stats.max_hp,
+            max_mana: 0,
+            mana: 0,
+            defense: template.stats.defense,
+            power: template.stats.power,
         })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
+
+    if let Some(natural) = template.natural {
+        let attacks = natural
+            .attacks
+            .iter()
+            .map(|attack| {
+                let dice = rltk::parse_dice_string(&attack.damage).expect("Invalid damage dice");
+                NaturalAttack {
+                    name: attack.name.clone(),
+                    hit_bonus: attack.hit_bonus,
+                    damage_n_dice: dice.n_dice,
+                    damage_die_type: dice.die_type,
+                    damage_bonus: dice.bonus,
+                    damage_type: attack.damage_type,
+                    strikes: attack.strikes,
+                }
+            })
+            .collect();
+        ecs.write_storage::<NaturalAttackDefense>()
+            .insert(
+                entity,
+                NaturalAttackDefense {
+                    armor: natural.armor,
+                    attacks,
+                },
+            )
+            .expect("Unable to insert natural attacks");
+    }
 }
 
 pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
-    ecs.create_entity()
+    let player = ecs
+        .create_entity()
         .with(Position {
             x: player_x,
             y: player_y,
@@ -296,9 +1273,26 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
         .with(CombatStats {
             max_hp: 30,
             hp: 30,
+            max_mana: 10,
+            mana: 10,
             defense: 2,
-            power: 5,
+            power: 0,
+        })
+        .with(Attributes::new(12, 12, 11, 10))
+        .with(Skills::default())
+        .with(Energy {
+            speed: NORMAL_SPEED,
+            current: ACTION_COST,
+        })
+        .with(Experience { level: 1, xp: 0 })
+        .with(HungerClock {
+            state: HungerState::WellFed,
+            duration: HUNGER_TURNS,
         })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build()
+        .build();
+
+    spell(ecs, "Magic Missile", player);
+
+    player
 }
@@ -0,0 +1,101 @@
+use super::{
+    spawner, CombatStats, GameLog, InSpellbook, Name, Spell, TeachesSpell, WantsToUseItem,
+};
+use specs::prelude::*;
+
+/// The spells an entity knows, cheapest first.
+pub fn known_spells(ecs: &World, owner: Entity) -> Vec<Entity> {
+    let entities = ecs.entities();
+    let spellbook = ecs.read_storage::<InSpellbook>();
+    let spells = ecs.read_storage::<Spell>();
+    let names = ecs.read_storage::<Name>();
+
+    let mut known: Vec<(Entity, i32, String)> = (&entities, &spellbook, &spells, &names)
+        .join()
+        .filter(|(_, book, _, _)| book.owner == owner)
+        .map(|(entity, _, spell, name)| (entity, spell.mana_cost, name.name.clone()))
+        .collect();
+    known.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.2.cmp(&b.2)));
+    known.into_iter().map(|(entity, _, _)| entity).collect()
+}
+
+/// Pays the mana for spells being cast and teaches the spells in spellbooks being read, before
+/// the ItemUseSystem applies their effects.
+pub struct SpellSystem {}
+
+impl<'a> System<'a> for SpellSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, WantsToUseItem>,
+        ReadStorage<'a, Spell>,
+        ReadStorage<'a, TeachesSpell>,
+        ReadStorage<'a, InSpellbook>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, CombatStats>,
+        Read<'a, LazyUpdate>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            mut gamelog,
+            mut wants_use,
+            spells,
+            teaches,
+            spellbook,
+            names,
+            mut combat_stats,
+            lazy,
+        ) = data;
+
+        let mut refused = Vec::new();
+        for (entity, useitem) in (&entities, &wants_use).join() {
+            if let Some(spell) = spells.get(useitem.item) {
+                match combat_stats.get_mut(entity) {
+                    Some(stats) if stats.mana >= spell.mana_cost => {
+                        stats.mana -= spell.mana_cost;
+                    }
+                    _ => {
+                        if entity == *player_entity {
+                            gamelog.entries.push(format!(
+                                "You don't have enough mana to cast {}.",
+                                names.get(useitem.item).unwrap().name
+                            ));
+                        }
+                        refused.push(entity);
+                    }
+                }
+            }
+
+            if let Some(book) = teaches.get(useitem.item) {
+                let known = (&spellbook, &names)
+                    .join()
+                    .any(|(known, name)| known.owner == entity && name.name == book.spell);
+                if known {
+                    if entity == *player_entity {
+                        gamelog
+                            .entries
+                            .push(format!("You already know {}.", book.spell));
+                    }
+                    refused.push(entity);
+                } else {
+                    if entity == *player_entity {
+                        gamelog
+                            .entries
+                            .push(format!("You learn to cast {}!", book.spell));
+                    }
+                    let spell = book.spell.clone();
+                    lazy.exec_mut(move |world| spawner::spell(world, &spell, entity));
+                }
+            }
+        }
+
+        for entity in refused {
+            wants_use.remove(entity);
+        }
+    }
+}
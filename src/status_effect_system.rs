@@ -0,0 +1,142 @@
+use super::{
+    initiative_system::TurnClock, ApplyStatus, CombatStats, DamageType, GameLog, RunState,
+    SerializeMe, StatusEffect, StatusKind, SufferDamage, Viewshed,
+};
+use specs::prelude::*;
+use specs::saveload::{MarkerAllocator, SimpleMarker, SimpleMarkerAllocator};
+use std::collections::HashSet;
+
+/// The set of (target, kind) pairs for every status effect currently active.
+pub fn active_statuses(statuses: &ReadStorage<StatusEffect>) -> HashSet<(Entity, StatusKind)> {
+    statuses.join().map(|s| (s.target, s.kind)).collect()
+}
+
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, TurnClock>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, StatusEffect>,
+        WriteStorage<'a, ApplyStatus>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, Viewshed>,
+        WriteStorage<'a, SimpleMarker<SerializeMe>>,
+        WriteExpect<'a, SimpleMarkerAllocator<SerializeMe>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            runstate,
+            clock,
+            player_entity,
+            mut log,
+            mut statuses,
+            mut pending,
+            mut suffer_damage,
+            mut combat_stats,
+            mut viewsheds,
+            mut markers,
+            mut allocator,
+        ) = data;
+
+        // Attach newly inflicted effects, refreshing those that don't stack
+        for (target, pending) in (&entities, &pending).join() {
+            for (kind, duration, magnitude, source) in pending.effects.iter() {
+                let existing = (&mut statuses)
+                    .join()
+                    .find(|s| s.target == target && s.kind == *kind);
+
+                match existing {
+                    Some(status) if !kind.stacks() => {
+                        status.duration = i32::max(status.duration, *duration);
+                        status.magnitude = i32::max(status.magnitude, *magnitude);
+                        status.source = source.or(status.source);
+                    }
+                    _ => {
+                        let effect = entities.create();
+                        statuses
+                            .insert(
+                                effect,
+                                StatusEffect {
+                                    target,
+                                    kind: *kind,
+                                    duration: *duration,
+                                    magnitude: *magnitude,
+                                    source: *source,
+                                },
+                            )
+                            .expect("Unable to insert status effect");
+                        allocator.mark(effect, &mut markers);
+                    }
+                }
+
+                if target == *player_entity {
+                    log.entries.push(format!("You are {}!", kind.name()));
+                }
+                if *kind == StatusKind::Blinded {
+                    if let Some(viewshed) = viewsheds.get_mut(target) {
+                        viewshed.dirty = true;
+                    }
+                }
+            }
+        }
+        pending.clear();
+
+        // Effects tick down once per turn, at the end of the monsters' turn
+        if *runstate != RunState::MonsterTurn || !clock.new_turn() {
+            return;
+        }
+
+        for (effect, status) in (&entities, &mut statuses).join() {
+            if !entities.is_alive(status.target) {
+                entities
+                    .delete(effect)
+                    .expect("Unable to delete status effect");
+                continue;
+            }
+
+            match status.kind {
+                StatusKind::Poisoned => {
+                    SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        status.target,
+                        status.magnitude,
+                        DamageType::Poison,
+                        "poison",
+                        status.source,
+                    );
+                }
+                StatusKind::Regenerating => {
+                    if let Some(stats) = combat_stats.get_mut(status.target) {
+                        stats.hp = i32::min(stats.max_hp, stats.hp + status.magnitude);
+                    }
+                }
+                _ => {}
+            }
+
+            status.duration -= 1;
+            if status.duration < 1 {
+                entities
+                    .delete(effect)
+                    .expect("Unable to delete status effect");
+
+                if status.target == *player_entity {
+                    log.entries
+                        .push(format!("You are no longer {}.", status.kind.name()));
+                }
+                if status.kind == StatusKind::Blinded {
+                    if let Some(viewshed) = viewsheds.get_mut(status.target) {
+                        viewshed.dirty = true;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use super::{
+    effects::{EffectQueue, EffectType, Targets},
+    EntityMoved, EntryTrigger, GameLog, Hidden, InflictsDamage, InflictsStatus, Map, Name,
+    Position, SingleActivation, TeleportsTarget,
+};
+use specs::prelude::*;
+
+/// Fires the entry triggers in every tile that something moved into this turn.
+pub struct TriggerSystem {}
+
+impl<'a> System<'a> for TriggerSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, EffectQueue>,
+        WriteStorage<'a, EntityMoved>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, EntryTrigger>,
+        WriteStorage<'a, Hidden>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, InflictsDamage>,
+        ReadStorage<'a, InflictsStatus>,
+        ReadStorage<'a, TeleportsTarget>,
+        ReadStorage<'a, SingleActivation>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            map,
+            player_entity,
+            mut log,
+            mut effects,
+            mut moved,
+            positions,
+            triggers,
+            mut hidden,
+            names,
+            inflicts_damage,
+            inflicts_status,
+            teleports,
+            single_activation,
+        ) = data;
+
+        for (entity, _moved, pos) in (&entities, &moved, &positions).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            for trap in map.tile_content[idx].iter() {
+                if *trap == entity || triggers.get(*trap).is_none() {
+                    continue;
+                }
+
+                // Whoever is watching now knows the trap is there
+                if entity == *player_entity || map.visible_tiles[idx] {
+                    hidden.remove(*trap);
+                    if let (Some(name), Some(trap_name)) = (names.get(entity), names.get(*trap)) {
+                        log.entries
+                            .push(format!("{} triggers a {}!", name.name, trap_name.name));
+                    }
+                }
+
+                let victim = Targets::Single { target: entity };
+                if let Some(damage) = inflicts_damage.get(*trap) {
+                    effects.add_effect(
+                        Some(*trap),
+                        EffectType::Damage {
+                            amount: damage.damage,
+                            damage_type: damage.damage_type,
+                        },
+                        victim.clone(),
+                    );
+                }
+                if let Some(status) = inflicts_status.get(*trap) {
+                    effects.add_effect(
+                        Some(*trap),
+                        EffectType::Status {
+                            kind: status.kind,
+                            duration: status.duration,
+                            magnitude: status.magnitude,
+                        },
+                        victim.clone(),
+                    );
+                }
+                if teleports.get(*trap).is_some() {
+                    effects.add_effect(Some(*trap), EffectType::Teleport, victim);
+                }
+                if single_activation.get(*trap).is_some() {
+                    entities.delete(*trap).expect("Unable to delete trap");
+                }
+            }
+        }
+        moved.clear();
+    }
+}
@@ -1,7 +1,14 @@
-use super::{Map, Player, Position, Viewshed};
-use rltk::{field_of_view, Point};
+use super::{
+    status_effect_system::active_statuses, Attributes, GameLog, Hidden, Map, Name, Player,
+    Position, StatusEffect, StatusKind, Viewshed,
+};
+use rltk::{field_of_view, Point, RandomNumberGenerator};
 use specs::prelude::*;
 
+/// Out of this many, the chance each turn of spotting a hidden thing in view, before the
+/// player's intelligence is taken into account.
+const SPOT_CHANCE: i32 = 24;
+
 pub struct VisibilitySystem {}
 
 impl<'a> System<'a> for VisibilitySystem {
@@ -11,22 +18,47 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteStorage<'a, Viewshed>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, Player>,
+        ReadStorage<'a, StatusEffect>,
+        WriteStorage<'a, Hidden>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Attributes>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, entities, mut viewshed, pos, player) = data;
+        let (
+            mut map,
+            entities,
+            mut viewshed,
+            pos,
+            player,
+            statuses,
+            mut hidden,
+            names,
+            attributes,
+            mut rng,
+            mut log,
+        ) = data;
+
+        let afflicted = active_statuses(&statuses);
 
         for (ent, viewshed, pos) in (&entities, &mut viewshed, &pos).join() {
             if viewshed.dirty {
+                // The blind can only make out what is right next to them
+                let range = if afflicted.contains(&(ent, StatusKind::Blinded)) {
+                    1
+                } else {
+                    viewshed.range
+                };
                 viewshed.visible_tiles.clear();
-                viewshed.visible_tiles =
-                    field_of_view(Point::new(pos.x, pos.y), viewshed.range, &*map);
+                viewshed.visible_tiles = field_of_view(Point::new(pos.x, pos.y), range, &*map);
                 viewshed.visible_tiles.retain(|p| {
                     p.x >= 0 && p.x < map.width - 1 && p.y >= 0 && p.y < map.height - 1
                 });
 
                 // If this is the player, reveal what they can see.
-                if let Some(_) = player.get(ent) {
+                if player.get(ent).is_some() {
                     for t in map.visible_tiles.iter_mut() {
                         *t = false
                     }
@@ -34,6 +66,20 @@ impl<'a> System<'a> for VisibilitySystem {
                         let idx = map.xy_idx(vis.x, vis.y);
                         map.revealed_tiles[idx] = true;
                         map.visible_tiles[idx] = true;
+
+                        // The sharp-witted may notice what is hidden
+                        let intelligence =
+                            attributes.get(ent).map_or(0, |a| a.intelligence.bonus());
+                        for e in map.tile_content[idx].iter() {
+                            if hidden.get(*e).is_some()
+                                && rng.roll_dice(1, SPOT_CHANCE) <= 1 + intelligence
+                            {
+                                if let Some(name) = names.get(*e) {
+                                    log.entries.push(format!("You spot a {}.", name.name));
+                                }
+                                hidden.remove(*e);
+                            }
+                        }
                     }
                 }
 